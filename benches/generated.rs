@@ -0,0 +1,79 @@
+//! Benchmarks for generated file detection.
+//!
+//! Compares `Generated::is_generated` against checking each filename
+//! pattern with its own regex, one after another.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fancy_regex::Regex;
+
+use linguist::generated::Generated;
+
+/// Number of paths in the benchmark corpus
+const CORPUS_SIZE: usize = 10_000;
+
+/// Build a corpus of paths, roughly one in ten of them generated
+fn corpus() -> Vec<String> {
+    let generated = [
+        "node_modules/lodash/index.js",
+        "ios/Pods/Alamofire/Source.swift",
+        "Cargo.lock",
+        "api/message.pb.go",
+        "project/.idea/workspace.xml",
+    ];
+    
+    (0..CORPUS_SIZE)
+        .map(|i| {
+            if i % 10 == 0 {
+                format!("pkg{}/{}", i, generated[i / 10 % generated.len()])
+            } else {
+                format!("src/module{}/component_{}/file{}.rs", i % 97, i % 13, i)
+            }
+        })
+        .collect()
+}
+
+/// Filename patterns checked one regex at a time
+fn sequential_patterns() -> Vec<Regex> {
+    [
+        r"\.(nib|xcworkspacedata|xcuserstate)$",
+        r"(?:^|\/)\.idea\/",
+        r"(^Pods|\/Pods)\/",
+        r"(^|\/)Carthage\/Build\/",
+        r"node_modules\/",
+        r"composer\.lock$",
+        r"Cargo\.lock$",
+        r"__generated__\/",
+        r"\.pb\.go$",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+}
+
+fn bench_generated_paths(c: &mut Criterion) {
+    let paths = corpus();
+    let patterns = sequential_patterns();
+    
+    let mut group = c.benchmark_group("generated_paths");
+    
+    group.bench_function("sequential_regexes", |b| {
+        b.iter(|| {
+            paths.iter()
+                .filter(|path| patterns.iter().any(|regex| regex.is_match(path).unwrap_or(false)))
+                .count()
+        })
+    });
+    
+    group.bench_function("is_generated", |b| {
+        b.iter(|| {
+            paths.iter()
+                .filter(|path| Generated::is_generated(black_box(path), &[]))
+                .count()
+        })
+    });
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_generated_paths);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+//! Benchmarks for repository analysis.
+//!
+//! Analyzes a commit with 10,000 files spread over 100 directories, once on
+//! a single rayon thread (the sequential `process_tree`) and once on four
+//! threads (`process_tree_parallel`).
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use git2::{Oid, Repository as GitRepository, Signature};
+
+use linguist::repository::Repository;
+
+/// Number of directories in the benchmark repository
+const DIRECTORIES: usize = 100;
+
+/// Number of files in each directory
+const FILES_PER_DIRECTORY: usize = 100;
+
+/// Number of rayon threads for the parallel analysis
+const PARALLEL_THREADS: usize = 4;
+
+/// Create a repository with a single commit of `DIRECTORIES * FILES_PER_DIRECTORY` files
+fn create_repository(path: &std::path::Path) -> Oid {
+    let repo = GitRepository::init(path).unwrap();
+    let sources = [
+        ("rs", "fn main() {\n    println!(\"hello\");\n}\n"),
+        ("py", "def main():\n    print('hello')\n"),
+        ("rb", "def main\n  puts 'hello'\nend\n"),
+        ("js", "function main() {\n  console.log('hello');\n}\n"),
+    ];
+    
+    let mut root = repo.treebuilder(None).unwrap();
+    for dir in 0..DIRECTORIES {
+        let mut tree = repo.treebuilder(None).unwrap();
+        for file in 0..FILES_PER_DIRECTORY {
+            let (ext, source) = sources[file % sources.len()];
+            let content = format!("// file {} in module {}\n{}", file, dir, source);
+            let oid = repo.blob(content.as_bytes()).unwrap();
+            tree.insert(format!("file{}.{}", file, ext), oid, 0o100644).unwrap();
+        }
+        root.insert(format!("module{}", dir), tree.write().unwrap(), 0o040000).unwrap();
+    }
+    
+    let tree = repo.find_tree(root.write().unwrap()).unwrap();
+    let signature = Signature::now("Bench", "bench@example.com").unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[]).unwrap()
+}
+
+fn bench_tree_walk(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let commit = create_repository(dir.path()).to_string();
+    let analyze = || {
+        let mut repo = Repository::new(dir.path(), &commit, None).unwrap();
+        assert_eq!(repo.languages().unwrap().len(), 4);
+    };
+    
+    let mut group = c.benchmark_group("tree_walk");
+    group.sample_size(10);
+    
+    let sequential = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    group.bench_function("sequential", |b| b.iter(|| sequential.install(analyze)));
+    let parallel = rayon::ThreadPoolBuilder::new().num_threads(PARALLEL_THREADS).build().unwrap();
+    group.bench_function("parallel", |b| b.iter(|| parallel.install(analyze)));
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_tree_walk);
+criterion_main!(benches);
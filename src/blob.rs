@@ -3,36 +3,48 @@
 //! This module provides traits and implementations for accessing and
 //! analyzing file contents, both from the filesystem and from git repositories.
 
-use std::cell::UnsafeCell;
 use std::fs::File;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use encoding_rs::Encoding;
 use encoding_rs_io::DecodeReaderBytesBuilder;
-use memmap2::Mmap;
+use memmap2::{Mmap, MmapOptions};
 use fancy_regex::Regex;
 
-use crate::generated::Generated;
-use crate::language::Language;
+pub use crate::data::comment_patterns::CommentPatterns;
+use crate::generated::{Generated, GeneratedReason};
+use crate::language::{Language, LanguageType};
+use crate::repository::GitAttributeOverride;
 use crate::{Error, Result};
 
 // Maximum size to consider for full analysis
 const MEGABYTE: usize = 1024 * 1024;
 
-lazy_static::lazy_static! {
-    // Regular expression patterns for vendored paths (from vendor.yml)
-    static ref VENDORED_REGEXP: Regex = {
-        let patterns = vec![
-            r"(^|/)cache/",
-            r"^[Dd]ependencies/",
-            r"(^|/)dist/",
-            // Add more patterns from vendor.yml here
-        ];
-        Regex::new(&patterns.join("|")).unwrap()
-    };
+// Files at least this large are memory-mapped instead of read into memory
+const MMAP_THRESHOLD: usize = MEGABYTE;
 
+// Maximum bytes to sample when guessing a file's encoding
+const ENCODING_CONSIDER_BYTES: usize = 64 * 1024;
+
+// Regular expression patterns for vendored paths (from vendor.yml)
+const VENDORED_PATTERNS: &[&str] = &[
+    r"(^|/)cache/",
+    r"^[Dd]ependencies/",
+    r"(^|/)dist/",
+    // Add more patterns from vendor.yml here
+];
+
+lazy_static::lazy_static! {
+    // All vendored path patterns, matched in a single pass
+    static ref VENDORED_REGEXP: Regex = Regex::new(&VENDORED_PATTERNS.join("|")).unwrap();
+    
+    // Each vendored path pattern on its own, to report which one matched
+    static ref VENDORED_REGEXPS: Vec<(&'static str, Regex)> = VENDORED_PATTERNS.iter()
+        .map(|pattern| (*pattern, Regex::new(pattern).unwrap()))
+        .collect();
+    
     // Regular expression patterns for documentation paths (from documentation.yml)
     static ref DOCUMENTATION_REGEXP: Regex = {
         let patterns = vec![
@@ -45,6 +57,232 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Guess the encoding of text from its byte frequencies
+///
+/// # Arguments
+///
+/// * `data` - The data, of which at most `ENCODING_CONSIDER_BYTES` are sampled
+///
+/// # Returns
+///
+/// * `(&Encoding, bool)` - The guessed encoding, and whether the guess is plausible
+fn guess_encoding(data: &[u8]) -> (&'static Encoding, bool) {
+    let sample_size = std::cmp::min(data.len(), ENCODING_CONSIDER_BYTES);
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(&data[..sample_size], sample_size == data.len());
+    detector.guess_assess(None, true)
+}
+
+/// Get the name of a blob from its path
+///
+/// Windows separators are replaced with forward slashes, so that the path
+/// patterns used for vendored and documentation files match on every platform.
+///
+/// # Arguments
+///
+/// * `path` - The path of the blob
+///
+/// # Returns
+///
+/// * `String` - The name
+fn name_from_path(path: &Path) -> String {
+    let name = path.to_string_lossy().to_string();
+    #[cfg(windows)]
+    let name = name.replace('\\', "/");
+    name
+}
+
+/// Find the vendored path pattern a path matches
+///
+/// # Arguments
+///
+/// * `name` - The path
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The first matching pattern, or None if the path isn't vendored
+fn vendored_pattern(name: &str) -> Option<&'static str> {
+    if !VENDORED_REGEXP.is_match(name).unwrap_or(false) {
+        return None;
+    }
+    
+    VENDORED_REGEXPS.iter()
+        .find(|(_, regex)| regex.is_match(name).unwrap_or(false))
+        .map(|(pattern, _)| *pattern)
+}
+
+/// Interpret data as UTF-8, tolerating a character cut off at the end
+///
+/// A prefix read from a larger file can end partway through a multi-byte
+/// character; that incomplete character is dropped instead of failing.
+///
+/// # Arguments
+///
+/// * `data` - The data
+/// * `truncated` - Whether `data` was cut from longer content
+///
+/// # Returns
+///
+/// * `Option<&str>` - The text, or None if the data isn't valid UTF-8
+fn valid_utf8(data: &[u8], truncated: bool) -> Option<&str> {
+    match std::str::from_utf8(data) {
+        Ok(text) => Some(text),
+        Err(err) if truncated && err.error_len().is_none() => {
+            // SAFETY: `from_utf8` validated the bytes before `valid_up_to()`
+            Some(unsafe { std::str::from_utf8_unchecked(&data[..err.valid_up_to()]) })
+        }
+        Err(_) => None,
+    }
+}
+
+/// Truncate a string without splitting a character
+///
+/// # Arguments
+///
+/// * `s` - The string to truncate
+/// * `max_bytes` - The maximum length in bytes
+///
+/// # Returns
+///
+/// * `&str` - The longest prefix of `s` that fits in `max_bytes`
+pub(crate) fn truncate_str(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// How certain a detected text encoding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingConfidence {
+    /// Identified by a byte order mark or by being valid UTF-8
+    Certain,
+    
+    /// Guessed from byte frequencies
+    Guessed,
+}
+
+/// Whether a blob counts towards language statistics, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeStats {
+    /// Whether the blob is included
+    pub included: bool,
+    
+    /// The reason the blob is included or excluded
+    pub reason: InclusionReason,
+}
+
+/// Reason a blob is included in or excluded from language statistics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InclusionReason {
+    /// The blob is included
+    Included,
+    
+    /// The blob is binary
+    Binary,
+    
+    /// The path matches a vendored pattern
+    Vendored {
+        /// The matching pattern from vendor.yml, or `linguist-vendored`
+        /// when set in `.gitattributes`
+        pattern: String,
+    },
+    
+    /// The blob is generated
+    Generated {
+        /// Why the blob is considered generated
+        reason: GeneratedReason,
+    },
+    
+    /// The path matches a documentation pattern
+    Documentation,
+    
+    /// The language is neither a programming nor a markup language, or is
+    /// marked as not detectable
+    UnsupportedLanguageType,
+    
+    /// No language could be detected
+    NoLanguageDetected,
+}
+
+impl IncludeStats {
+    /// Create the result for a reason
+    fn from_reason(reason: InclusionReason) -> Self {
+        Self {
+            included: reason == InclusionReason::Included,
+            reason,
+        }
+    }
+}
+
+/// Decide whether a blob counts towards language statistics
+///
+/// This is the single implementation of the inclusion rules, shared by
+/// `BlobHelper::detect_include_stats` and repository analysis.
+///
+/// # Arguments
+///
+/// * `blob` - The blob to check
+/// * `attributes` - `.gitattributes` overrides that apply to the blob's path
+///
+/// # Returns
+///
+/// * `(InclusionReason, Option<Language>)` - The reason, and the blob's language if one was determined
+pub(crate) fn include_stats<B: BlobHelper + ?Sized>(
+    blob: &B,
+    attributes: &GitAttributeOverride,
+) -> (InclusionReason, Option<Language>) {
+    let vendored = match attributes.vendored {
+        Some(true) => Some("linguist-vendored"),
+        Some(false) => None,
+        None => vendored_pattern(blob.name()),
+    };
+    if let Some(pattern) = vendored {
+        return (InclusionReason::Vendored { pattern: pattern.to_string() }, None);
+    }
+    
+    if attributes.documentation.unwrap_or_else(|| blob.is_documentation()) {
+        return (InclusionReason::Documentation, None);
+    }
+    
+    let generated = match attributes.generated {
+        Some(true) => Some(GeneratedReason::Attribute),
+        Some(false) => None,
+        None => Generated::generated_reason(blob.name(), blob.data()),
+    };
+    if let Some(reason) = generated {
+        return (InclusionReason::Generated { reason }, None);
+    }
+    
+    let language = match &attributes.language {
+        Some(name) => Language::lookup(name).cloned(),
+        None if blob.is_binary() => return (InclusionReason::Binary, None),
+        None => blob.language(),
+    };
+    let language = match language {
+        Some(language) => language,
+        None => return (InclusionReason::NoLanguageDetected, None),
+    };
+    
+    // Only programming and markup languages are detectable by default
+    let detectable = attributes.detectable.unwrap_or(matches!(
+        language.language_type,
+        LanguageType::Programming | LanguageType::Markup
+    ));
+    let reason = if detectable {
+        InclusionReason::Included
+    } else {
+        InclusionReason::UnsupportedLanguageType
+    };
+    
+    (reason, Some(language))
+}
+
 /// Trait for objects that provide blob-like functionality
 
 pub trait BlobHelper {
@@ -60,9 +298,48 @@ pub trait BlobHelper {
     /// Get the file data
     fn data(&self) -> &[u8];
     
+    /// Get the file data as a string, without copying it
+    ///
+    /// When only a prefix was read, a character cut off at the end of the
+    /// prefix is left out.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The data, or None if it isn't valid UTF-8
+    fn try_as_utf8(&self) -> Option<&str> {
+        valid_utf8(self.data(), self.truncated())
+    }
+    
+    /// Get the start of the file data as a string, validating only that part
+    ///
+    /// # Arguments
+    ///
+    /// * `max_bytes` - The maximum length of the prefix in bytes
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The prefix without any character cut off at its end,
+    ///   or None if the prefix isn't valid UTF-8
+    fn utf8_prefix(&self, max_bytes: usize) -> Option<&str> {
+        let data = self.data();
+        let end = data.len().min(max_bytes);
+        valid_utf8(&data[..end], self.truncated() || end < data.len())
+    }
+    
     /// Get the size of the blob in bytes
+    ///
+    /// This is the full size, even when `data()` only holds a prefix.
     fn size(&self) -> usize;
     
+    /// Check if only a prefix of the blob was read
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if `data()` is shorter than `size()`
+    fn truncated(&self) -> bool {
+        false
+    }
+    
     /// Check if the blob is a symlink
     fn is_symlink(&self) -> bool;
     
@@ -114,19 +391,13 @@ pub trait BlobHelper {
             return Vec::new();
         }
         
-        // Convert to UTF-8 string
-        let content = match std::str::from_utf8(self.data()) {
-            Ok(s) => s.to_string(),
-            Err(_) => {
-                // Try to detect encoding and convert
-                match self.encoding() {
-                    Some((encoding, _)) => {
-                        let (cow, _, _) = encoding.decode(self.data());
-                        cow.into_owned()
-                    }
-                    None => return Vec::new(), // Cannot decode
-                }
+        // Decode with the detected encoding
+        let content = match self.encoding() {
+            Some((encoding, _)) => {
+                let (cow, _, _) = encoding.decode(self.data());
+                cow.into_owned()
             }
+            None => return Vec::new(), // Cannot decode
         };
         
         content.lines().map(String::from).collect()
@@ -153,30 +424,90 @@ pub trait BlobHelper {
         self.lines().len()
     }
     
+    /// Get the number of characters (Unicode scalar values)
+    ///
+    /// Falls back to the byte count when the data isn't valid UTF-8.
+    fn char_count(&self) -> usize {
+        self.try_as_utf8()
+            .map(|s| s.chars().count())
+            .unwrap_or(self.data().len())
+    }
+    
     /// Get the number of non-empty lines
     fn sloc(&self) -> usize {
         self.lines().iter().filter(|line| !line.trim().is_empty()).count()
     }
     
+    /// Get the number of lines that are neither blank nor comments
+    ///
+    /// Lines mixing code and a trailing comment count as code.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment_patterns` - The comment syntax of the blob's language
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The number of code lines, or None for binary blobs
+    fn sloc_by_language(&self, comment_patterns: &CommentPatterns) -> Option<usize> {
+        if self.is_binary() {
+            return None;
+        }
+        
+        let mut in_block = false;
+        let count = self.lines().iter()
+            .filter(|line| comment_patterns.is_code_line(line, &mut in_block))
+            .count();
+        
+        Some(count)
+    }
+    
     /// Try to detect the encoding of the file
-    fn encoding(&self) -> Option<(&'static Encoding, u32)> {
+    ///
+    /// A byte order mark or valid UTF-8 identifies the encoding with
+    /// certainty. Otherwise the encoding is guessed from byte frequencies
+    /// with `chardetng`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(&Encoding, EncodingConfidence)>` - The encoding and how it was
+    ///   determined, or None for binary and empty files
+    fn encoding(&self) -> Option<(&'static Encoding, EncodingConfidence)> {
         if self.is_binary() || self.is_empty() {
             return None;
         }
         
-        let (encoding, confidence) = encoding_rs::Encoding::for_bom(self.data())
-            .or_else(|| {
-                // Try charset detection with a limited sample
-                let sample_size = std::cmp::min(self.data().len(), 4096);
-                let sample = &self.data()[..sample_size];
-                
-                // Here we would use an encoding detector similar to CharlockHolmes
-                // For simplicity, we'll just default to UTF-8 with medium confidence
-                Some((encoding_rs::UTF_8, 60))
-            })
-            ?;
-            
-        Some((encoding, confidence.try_into().unwrap()))
+        if let Some((encoding, _)) = Encoding::for_bom(self.data()) {
+            return Some((encoding, EncodingConfidence::Certain));
+        }
+        
+        if self.try_as_utf8().is_some() {
+            return Some((encoding_rs::UTF_8, EncodingConfidence::Certain));
+        }
+        
+        // Text blobs always have a plausible guess; implausible ones are binary
+        let (encoding, _) = guess_encoding(self.data());
+        Some((encoding, EncodingConfidence::Guessed))
+    }
+    
+    /// Get the confidence of the detected encoding
+    ///
+    /// # Returns
+    ///
+    /// * `Option<EncodingConfidence>` - How the encoding was determined, or None if no
+    ///   encoding was detected
+    fn encoding_confidence(&self) -> Option<EncodingConfidence> {
+        self.encoding().map(|(_, confidence)| confidence)
+    }
+    
+    /// Get a language forced on the blob, bypassing detection
+    fn language_override(&self) -> Option<&Language> {
+        None
+    }
+    
+    /// Get the HTTP `Content-Type` header the blob was served with, if any
+    fn content_type(&self) -> Option<&str> {
+        None
     }
     
     /// Get the language of the blob
@@ -184,36 +515,140 @@ pub trait BlobHelper {
         crate::detect(self, false)
     }
     
+    /// Get the language of the blob without the slower content-based strategies
+    ///
+    /// See `crate::detect_fast`; use this for interactive lookups where
+    /// speed matters more than classifying ambiguous files.
+    fn detect_language_fast(&self) -> Option<Language> {
+        crate::detect_fast(self, false)
+    }
+    
+    /// Detect the language of the blob using the given detection options
+    fn detect_with_config(&self, config: &crate::DetectionConfig) -> Option<Language> {
+        crate::detect_with_config(self, config)
+    }
+    
     /// Check if the blob should be included in language statistics
     fn include_in_language_stats(&self) -> bool {
-        if self.is_vendored() || self.is_documentation() || self.is_generated() {
-            return false;
+        self.detect_include_stats().included
+    }
+    
+    /// Check if the blob should be included in language statistics, and why
+    ///
+    /// # Returns
+    ///
+    /// * `IncludeStats` - Whether the blob is included, with the deciding reason
+    fn detect_include_stats(&self) -> IncludeStats {
+        let (reason, _) = include_stats(self, &GitAttributeOverride::default());
+        IncludeStats::from_reason(reason)
+    }
+}
+
+/// The content of a FileBlob, either owned or mapped from the file
+enum BlobData {
+    /// Content read into memory
+    Owned(Vec<u8>),
+    
+    /// Content mapped from a large file
+    Mapped(Mmap),
+}
+
+impl std::ops::Deref for BlobData {
+    type Target = [u8];
+    
+    fn deref(&self) -> &[u8] {
+        match self {
+            BlobData::Owned(data) => data,
+            BlobData::Mapped(mmap) => mmap,
         }
+    }
+}
+
+impl BlobData {
+    /// Read up to `max_bytes` of a file, mapping them into memory if they are
+    /// at least `MMAP_THRESHOLD` bytes
+    ///
+    /// Falls back to reading the file when mapping fails, e.g. on file
+    /// systems that don't support it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to read
+    /// * `max_bytes` - The maximum number of bytes to read
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(BlobData, usize)>` - The content and the full size of the file,
+    ///   or an error if the file can't be read
+    fn read(path: &Path, max_bytes: usize) -> Result<(Self, usize)> {
+        let file = File::open(path)?;
+        let file_size = file.metadata()?.len() as usize;
+        let len = file_size.min(max_bytes);
         
-        if let Some(language) = self.language() {
-            // Only include programming and markup languages
-            matches!(language.language_type, 
-                crate::language::LanguageType::Programming | 
-                crate::language::LanguageType::Markup)
-        } else {
-            false
+        if len >= MMAP_THRESHOLD {
+            // SAFETY: the mapping is only read. As with any mmap, the file
+            // must not be truncated or modified while the blob is alive.
+            match unsafe { MmapOptions::new().len(len).map(&file) } {
+                Ok(mmap) => return Ok((BlobData::Mapped(mmap), file_size)),
+                Err(err) => log::debug!("Failed to map {}, reading it instead: {}", path.display(), err),
+            }
         }
+        
+        // Some files, e.g. in /proc, report a size of 0 but still have content
+        let mut buffer = Vec::with_capacity(len);
+        file.take(max_bytes as u64).read_to_end(&mut buffer)?;
+        let file_size = file_size.max(buffer.len());
+        Ok((BlobData::Owned(buffer), file_size))
     }
 }
 
 /// A blob implementation for files on disk
+///
+/// ```no_run
+/// use linguist::blob::{BlobHelper, FileBlob};
+/// use linguist::DetectionConfig;
+///
+/// let blob = FileBlob::new("src/main.rs").unwrap();
+/// let config = DetectionConfig { allow_empty: true, ..Default::default() };
+/// let language = blob.detect_with_config(&config);
+/// ```
 pub struct FileBlob {
     path: PathBuf,
     name: String,
-    data: Vec<u8>,
+    data: BlobData,
+    file_size: usize,
     symlink: bool,
+    override_language: Option<Language>,
+    content_type: Option<String>,
+    utf8: OnceLock<Option<usize>>,
 }
 
 impl FileBlob {
     /// Create a new FileBlob from a path
+    ///
+    /// Files of at least 1 MiB are memory-mapped rather than read, so
+    /// large files that end up skipped are never copied into memory.
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_limit(path, usize::MAX)
+    }
+    
+    /// Create a new FileBlob from a path, reading at most `max_bytes` of it
+    ///
+    /// Detection only needs the start of a file, so this keeps memory use
+    /// bounded for huge files such as logs. `size()` still reports the full
+    /// size on disk, while `data()` returns the prefix that was read.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to read
+    /// * `max_bytes` - The maximum number of bytes to read
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FileBlob>` - The blob, or an error if the file can't be read
+    pub fn with_limit<P: AsRef<Path>>(path: P, max_bytes: usize) -> Result<Self> {
         let path = path.as_ref();
-        let name = path.to_string_lossy().to_string();
+        let name = name_from_path(path);
         
         // Check if it's a symlink
         let symlink = path.symlink_metadata()
@@ -221,35 +656,142 @@ impl FileBlob {
             .unwrap_or(false);
         
         // Read the file
-        let data = if symlink {
-            Vec::new()
+        let (data, file_size) = if symlink {
+            (BlobData::Owned(Vec::new()), 0)
         } else {
-            let mut file = File::open(path)?;
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer)?;
-            buffer
+            BlobData::read(path, max_bytes)?
         };
         
         Ok(Self {
             path: path.to_path_buf(),
             name,
             data,
+            file_size,
             symlink,
+            override_language: None,
+            content_type: None,
+            utf8: OnceLock::new(),
         })
     }
     
     /// Create a new FileBlob with in-memory data
     pub fn from_data<P: AsRef<Path>>(path: P, data: Vec<u8>) -> Self {
         let path = path.as_ref();
-        let name = path.to_string_lossy().to_string();
+        let name = name_from_path(path);
         
         Self {
             path: path.to_path_buf(),
             name,
-            data,
+            file_size: data.len(),
+            data: BlobData::Owned(data),
             symlink: false,
+            override_language: None,
+            content_type: None,
+            utf8: OnceLock::new(),
         }
     }
+    
+    /// Create a new FileBlob from a file, detecting it under another name
+    ///
+    /// Useful when a file is stored under a temporary name (e.g. an upload
+    /// at `/tmp/upload_12345`) but its real name is known.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to read
+    /// * `name` - The name to use for detection, e.g. `config.yaml`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FileBlob>` - The blob, or an error if the file can't be read
+    pub fn from_path_with_override_name(path: &Path, name: &str) -> Result<Self> {
+        let mut blob = Self::new(path)?;
+        blob.path = PathBuf::from(name);
+        blob.name = name_from_path(&blob.path);
+        Ok(blob)
+    }
+    
+    /// Create a new FileBlob from a git blob, reading its content immediately
+    ///
+    /// Unlike `LazyBlob`, the content is loaded up front, which suits small
+    /// repositories where memory use isn't a concern.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The Git repository
+    /// * `oid` - The ID of the blob object
+    /// * `path` - The repository-relative path of the blob
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FileBlob>` - The blob, or an error if the object isn't a blob
+    pub fn from_git_object(repo: Arc<git2::Repository>, oid: git2::Oid, path: &str) -> Result<Self> {
+        let blob = repo.find_blob(oid)?;
+        Ok(Self::from_data(path, blob.content().to_vec()))
+    }
+    
+    /// Force the language of this blob instead of detecting it
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The name of the language to use
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - The blob, or `Error::UnknownLanguage` if no language has that name
+    pub fn with_language_override(mut self, language: &str) -> Result<Self> {
+        let language = Language::find_by_name(language)
+            .ok_or_else(|| Error::UnknownLanguage(language.to_string()))?;
+        
+        self.override_language = Some(language.clone());
+        Ok(self)
+    }
+    
+    /// Attach the HTTP `Content-Type` header the blob was fetched with
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header value, e.g. `text/x-ruby; charset=utf-8`
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The blob
+    pub fn with_content_type(mut self, header: &str) -> Self {
+        self.content_type = Some(header.to_string());
+        self
+    }
+    
+    /// Check whether the blob's content is memory-mapped from the file
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the content is mapped, false if it was read into memory
+    pub fn is_memory_mapped(&self) -> bool {
+        matches!(self.data, BlobData::Mapped(_))
+    }
+    
+    /// Get a fast, non-cryptographic hash of the blob's content (xxHash3)
+    ///
+    /// Suitable for cache keys, not for integrity checks.
+    #[cfg(feature = "fingerprint")]
+    pub fn content_fingerprint(&self) -> u64 {
+        xxhash_rust::xxh3::xxh3_64(&self.data)
+    }
+    
+    /// Get a fast hash of the first bytes of the blob's content (xxHash3)
+    ///
+    /// # Arguments
+    ///
+    /// * `first_bytes` - Number of bytes to hash; the whole content if it is shorter
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The fingerprint
+    #[cfg(feature = "fingerprint")]
+    pub fn partial_fingerprint(&self, first_bytes: usize) -> u64 {
+        let end = std::cmp::min(first_bytes, self.data.len());
+        xxhash_rust::xxh3::xxh3_64(&self.data[..end])
+    }
 }
 
 impl BlobHelper for FileBlob {
@@ -290,14 +832,46 @@ impl BlobHelper for FileBlob {
         &self.data
     }
     
+    fn try_as_utf8(&self) -> Option<&str> {
+        // Validate once; the data never changes after construction
+        let len = *self.utf8.get_or_init(|| valid_utf8(&self.data, self.truncated()).map(str::len));
+        // SAFETY: the first `len` bytes of `self.data` were validated as UTF-8 above and are never mutated through the blob
+        len.map(|len| unsafe { std::str::from_utf8_unchecked(&self.data[..len]) })
+    }
+    
+    fn utf8_prefix(&self, max_bytes: usize) -> Option<&str> {
+        if max_bytes >= self.data.len() {
+            return self.try_as_utf8();
+        }
+        
+        // Reuse the validation of the whole data when it has already passed
+        if let Some(Some(_)) = self.utf8.get() {
+            return self.try_as_utf8().map(|text| truncate_str(text, max_bytes));
+        }
+        
+        valid_utf8(&self.data[..max_bytes], true)
+    }
+    
     fn size(&self) -> usize {
-        self.data.len()
+        self.file_size
+    }
+    
+    fn truncated(&self) -> bool {
+        self.data.len() < self.file_size
     }
     
     fn is_symlink(&self) -> bool {
         self.symlink
     }
     
+    fn language_override(&self) -> Option<&Language> {
+        self.override_language.as_ref()
+    }
+    
+    fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+    
     fn is_binary(&self) -> bool {
         // Check for null bytes or non-UTF-8 sequences
         if self.data.is_empty() {
@@ -309,10 +883,10 @@ impl BlobHelper for FileBlob {
             return true;
         }
         
-        // Try to interpret as UTF-8
-        match std::str::from_utf8(&self.data) {
-            Ok(_) => false, // Valid UTF-8 is considered text
-            Err(_) => true,  // Invalid UTF-8 is considered binary
+        // Try to interpret as UTF-8, then as a legacy text encoding
+        match self.try_as_utf8() {
+            Some(_) => false, // Valid UTF-8 is considered text
+            None => !guess_encoding(&self.data).1,
         }
     }
     
@@ -333,13 +907,17 @@ impl BlobHelper for FileBlob {
 }
 
 /// A blob implementation for lazy-loaded git blobs
+///
+/// The content is read from the object database on first use and cached.
+/// Like `git2::Repository`, a LazyBlob can't be shared between threads;
+/// open a repository per thread instead.
 pub struct LazyBlob {
     repo: Arc<git2::Repository>,
     oid: git2::Oid,
     path: String,
     mode: Option<String>,
-    data: UnsafeCell<Option<Vec<u8>>>,
-    size: UnsafeCell<Option<usize>>,
+    data: OnceLock<Vec<u8>>,
+    size: OnceLock<usize>,
 }
 
 impl LazyBlob {
@@ -350,29 +928,29 @@ impl LazyBlob {
             oid,
             path,
             mode,
-            data: UnsafeCell::new(None),
-            size: UnsafeCell::new(None),
+            data: OnceLock::new(),
+            size: OnceLock::new(),
         }
     }
     
-    /// Load the blob data if not already loaded
-    fn load_blob(&self) -> Result<()> {
-        // Safety: We're ensuring internal mutability in a controlled way
-        // This is safe because we're only modifying the internal state when needed,
-        // and the modification is not visible to the outside world other than
-        // through the APIs we control
-        unsafe {
-            let data_ptr = self.data.get();
-            let size_ptr = self.size.get();
-            
-            if (*data_ptr).is_none() {
-                let blob = self.repo.find_blob(self.oid)?;
-                let blob_data = blob.content().to_vec();
-                *size_ptr = Some(blob_data.len());
-                *data_ptr = Some(blob_data);
-            }
-        }
-        Ok(())
+    /// Load the blob data from the object database
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>>` - The content, or an error if the object isn't a readable blob
+    fn load_blob(&self) -> Result<Vec<u8>> {
+        let blob = self.repo.find_blob(self.oid)?;
+        Ok(blob.content().to_vec())
+    }
+    
+    /// Read the blob size from the object header, without loading the content
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize>` - The size in bytes, or an error if the object can't be read
+    fn read_size(&self) -> Result<usize> {
+        let (size, _) = self.repo.odb()?.read_header(self.oid)?;
+        Ok(size)
     }
 }
 
@@ -413,32 +991,15 @@ impl BlobHelper for LazyBlob {
     }
     
     fn data(&self) -> &[u8] {
-        // First, ensure the data is loaded
-        if let Err(_) = self.load_blob() {
-            return &[];
-        }
-        
-        // Safety: We know the data exists because we just loaded it,
-        // and we're only returning an immutable reference to it
-        unsafe {
-            if let Some(ref data) = *self.data.get() {
-                data
-            } else {
-                &[]
-            }
-        }
+        // Unreadable blobs are treated as empty
+        self.data.get_or_init(|| self.load_blob().unwrap_or_default())
     }
     
     fn size(&self) -> usize {
-        // If size is already calculated, return it
-        unsafe {
-            if let Some(size) = *self.size.get() {
-                return size;
-            }
-        }
-        
-        // Otherwise, ensure data is loaded and return its length
-        self.data().len()
+        *self.size.get_or_init(|| match self.data.get() {
+            Some(data) => data.len(),
+            None => self.read_size().unwrap_or_else(|_| self.data().len()),
+        })
     }
     
     // Other methods remain unchanged
@@ -466,10 +1027,10 @@ impl BlobHelper for LazyBlob {
             return true;
         }
         
-        // Try to interpret as UTF-8
+        // Try to interpret as UTF-8, then as a legacy text encoding
         match std::str::from_utf8(data) {
             Ok(_) => false, // Valid UTF-8 is considered text
-            Err(_) => true,  // Invalid UTF-8 is considered binary
+            Err(_) => !guess_encoding(data).1,
         }
     }
     
@@ -541,6 +1102,41 @@ mod tests {
         Ok(())
     }
     
+    #[test]
+    fn test_detect_include_stats() {
+        let reason = |name: &str, data: &[u8]| FileBlob::from_data(Path::new(name), data.to_vec()).detect_include_stats().reason;
+        
+        let stats = FileBlob::from_data(Path::new("main.rs"), b"fn main() {}\n".to_vec()).detect_include_stats();
+        assert_eq!(stats, IncludeStats { included: true, reason: InclusionReason::Included });
+        
+        assert_eq!(reason("dist/app.js", b"var x;\n"), InclusionReason::Vendored { pattern: r"(^|/)dist/".to_string() });
+        assert_eq!(reason("docs/guide.rs", b"fn main() {}\n"), InclusionReason::Documentation);
+        assert_eq!(
+            reason("Cargo.lock", b"version = 3\n"),
+            InclusionReason::Generated { reason: GeneratedReason::Path { pattern: "Cargo.lock".to_string() } }
+        );
+        assert_eq!(reason("image.bin", &[0, 1, 2, 3]), InclusionReason::Binary);
+        assert_eq!(reason("config.json", b"{}\n"), InclusionReason::UnsupportedLanguageType);
+        assert_eq!(reason("notes.unknownext", b"just words\n"), InclusionReason::NoLanguageDetected);
+        
+        let stats = FileBlob::from_data(Path::new("config.json"), b"{}\n".to_vec()).detect_include_stats();
+        assert!(!stats.included);
+        
+        // .gitattributes overrides go through the same rules
+        let blob = FileBlob::from_data(Path::new("dist/app.js"), b"var x;\n".to_vec());
+        let attributes = GitAttributeOverride { vendored: Some(false), ..Default::default() };
+        let (reason, language) = include_stats(&blob, &attributes);
+        assert_eq!(reason, InclusionReason::Included);
+        assert_eq!(language.unwrap().name, "JavaScript");
+        
+        let blob = FileBlob::from_data(Path::new("main.rs"), b"fn main() {}\n".to_vec());
+        let attributes = GitAttributeOverride { generated: Some(true), ..Default::default() };
+        assert_eq!(include_stats(&blob, &attributes).0, InclusionReason::Generated { reason: GeneratedReason::Attribute });
+        
+        let attributes = GitAttributeOverride { detectable: Some(false), ..Default::default() };
+        assert_eq!(include_stats(&blob, &attributes).0, InclusionReason::UnsupportedLanguageType);
+    }
+    
     #[test]
     fn test_binary_detection() -> Result<()> {
         let dir = tempdir()?;
@@ -558,4 +1154,264 @@ mod tests {
         
         Ok(())
     }
+    
+    #[test]
+    fn test_language_override() -> Result<()> {
+        let blob = FileBlob::from_data(Path::new("script.rb"), b"puts 'hello'".to_vec());
+        assert_eq!(blob.language().unwrap().name, "Ruby");
+        
+        let blob = blob.with_language_override("Python")?;
+        assert_eq!(blob.language().unwrap().name, "Python");
+        
+        let blob = FileBlob::from_data(Path::new("script.rb"), Vec::new());
+        assert!(matches!(
+            blob.with_language_override("NotALanguage"),
+            Err(Error::UnknownLanguage(name)) if name == "NotALanguage"
+        ));
+        
+        Ok(())
+    }
+    
+    #[cfg(feature = "fingerprint")]
+    #[test]
+    fn test_content_fingerprint() {
+        let blob = FileBlob::from_data(Path::new("a.txt"), b"hello world".to_vec());
+        let same = FileBlob::from_data(Path::new("b.txt"), b"hello world".to_vec());
+        let other = FileBlob::from_data(Path::new("a.txt"), b"hello there".to_vec());
+        
+        assert_eq!(blob.content_fingerprint(), same.content_fingerprint());
+        assert_ne!(blob.content_fingerprint(), other.content_fingerprint());
+        
+        assert_eq!(blob.partial_fingerprint(6), other.partial_fingerprint(6));
+        assert_eq!(blob.partial_fingerprint(1000), blob.content_fingerprint());
+    }
+    
+    #[test]
+    fn test_from_path_with_override_name() -> Result<()> {
+        let dir = tempdir()?;
+        let upload = dir.path().join("upload_12345");
+        std::fs::write(&upload, "name: linguist\nversion: 1\n")?;
+        
+        let blob = FileBlob::from_path_with_override_name(&upload, "config.yaml")?;
+        assert_eq!(blob.name(), "config.yaml");
+        assert_eq!(blob.extension().as_deref(), Some(".yaml"));
+        assert_eq!(blob.language().unwrap().name, "YAML");
+        
+        let blob = FileBlob::from_path_with_override_name(&upload, "dist/config.yaml")?;
+        assert!(blob.is_vendored());
+        
+        assert!(FileBlob::from_path_with_override_name(&dir.path().join("missing"), "a.yaml").is_err());
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_memory_mapped() -> Result<()> {
+        let dir = tempdir()?;
+        let line = "func main() { fmt.Println(\"hello\") }\n";
+        let content = format!("package main\n\nimport \"fmt\"\n\n{}", line.repeat(8 * MEGABYTE / line.len()));
+        
+        let large = dir.path().join("large.go");
+        std::fs::write(&large, &content)?;
+        
+        let blob = FileBlob::new(&large)?;
+        assert!(blob.is_memory_mapped());
+        assert_eq!(blob.size(), content.len());
+        assert_eq!(blob.data(), content.as_bytes());
+        assert!(!blob.is_binary());
+        assert_eq!(blob.loc(), content.lines().count());
+        assert_eq!(blob.language().unwrap().name, "Go");
+        
+        let small = dir.path().join("small.go");
+        std::fs::write(&small, "package main\n")?;
+        
+        let blob = FileBlob::new(&small)?;
+        assert!(!blob.is_memory_mapped());
+        assert_eq!(blob.data(), b"package main\n");
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_with_limit() -> Result<()> {
+        let dir = tempdir()?;
+        let content = "puts 'hello'\n".repeat(1000);
+        let path = dir.path().join("script.rb");
+        std::fs::write(&path, &content)?;
+        
+        let blob = FileBlob::with_limit(&path, 100)?;
+        assert!(blob.truncated());
+        assert_eq!(blob.data(), &content.as_bytes()[..100]);
+        assert_eq!(blob.size(), content.len());
+        assert_eq!(blob.language().unwrap().name, "Ruby");
+        
+        let blob = FileBlob::with_limit(&path, content.len())?;
+        assert!(!blob.truncated());
+        assert_eq!(blob.size(), content.len());
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_with_limit_splitting_character() -> Result<()> {
+        let dir = tempdir()?;
+        let content = "# héllo\n".repeat(10);
+        let path = dir.path().join("script.rb");
+        std::fs::write(&path, &content)?;
+        
+        // The limit falls between the two bytes of `é`
+        let blob = FileBlob::with_limit(&path, 4)?;
+        assert!(blob.truncated());
+        assert_eq!(blob.try_as_utf8(), Some("# h"));
+        assert!(!blob.is_binary());
+        assert_eq!(blob.encoding_confidence(), Some(EncodingConfidence::Certain));
+        
+        // Complete data with an incomplete character is still invalid
+        let blob = FileBlob::from_data(Path::new("script.rb"), content.as_bytes()[..4].to_vec());
+        assert!(!blob.truncated());
+        assert_eq!(blob.try_as_utf8(), None);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_from_git_object() -> Result<()> {
+        let dir = tempdir()?;
+        let repo = Arc::new(git2::Repository::init(dir.path())?);
+        let oid = repo.blob(b"def hello\n  puts 'hello'\nend\n")?;
+        
+        let blob = FileBlob::from_git_object(repo.clone(), oid, "lib/hello.rb")?;
+        assert_eq!(blob.name(), "lib/hello.rb");
+        assert_eq!(blob.data(), b"def hello\n  puts 'hello'\nend\n");
+        assert_eq!(blob.language().unwrap().name, "Ruby");
+        
+        // Tree objects are not blobs
+        let tree_oid = repo.treebuilder(None)?.write()?;
+        assert!(FileBlob::from_git_object(repo, tree_oid, "lib").is_err());
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_lazy_blob() -> Result<()> {
+        let dir = tempdir()?;
+        let repo = Arc::new(git2::Repository::init(dir.path())?);
+        let content = b"def hello\n  puts 'hello'\nend\n";
+        let oid = repo.blob(content)?;
+        
+        // The size comes from the object header, without loading the content
+        let blob = LazyBlob::new(repo.clone(), oid, "lib/hello.rb".to_string(), Some("100644".to_string()));
+        assert_eq!(blob.size(), content.len());
+        assert!(blob.data.get().is_none());
+        
+        // The content is loaded once and reused
+        assert_eq!(blob.data(), content);
+        assert!(std::ptr::eq(blob.data(), blob.data()));
+        assert_eq!(blob.language().unwrap().name, "Ruby");
+        
+        let missing = LazyBlob::new(repo, git2::Oid::zero(), "missing.rb".to_string(), None);
+        assert!(missing.data().is_empty());
+        assert_eq!(missing.size(), 0);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_encoding() {
+        let blob = FileBlob::from_data(Path::new("hello.txt"), "héllo\nwörld\n".as_bytes().to_vec());
+        assert_eq!(blob.encoding(), Some((encoding_rs::UTF_8, EncodingConfidence::Certain)));
+        assert_eq!(blob.encoding_confidence(), Some(EncodingConfidence::Certain));
+        
+        // "Le café est très économique à Noël" in windows-1252
+        let (latin1, _, _) = encoding_rs::WINDOWS_1252.encode("Le café est très économique à Noël.\nDéjà vu.\n");
+        let blob = FileBlob::from_data(Path::new("notes.txt"), latin1.into_owned());
+        assert!(!blob.is_binary());
+        assert_eq!(blob.encoding(), Some((encoding_rs::WINDOWS_1252, EncodingConfidence::Guessed)));
+        assert_eq!(blob.lines(), vec!["Le café est très économique à Noël.", "Déjà vu."]);
+        
+        let blob = FileBlob::from_data(Path::new("empty.txt"), Vec::new());
+        assert_eq!(blob.encoding_confidence(), None);
+    }
+    
+    #[test]
+    fn test_char_count() {
+        let blob = FileBlob::from_data(Path::new("hello.txt"), "héllo 世界 🦀".as_bytes().to_vec());
+        assert_eq!(blob.size(), 18);
+        assert_eq!(blob.char_count(), 10);
+        
+        // Invalid UTF-8 counts bytes
+        let blob = FileBlob::from_data(Path::new("data.bin"), vec![0xff, 0xfe, 0x41]);
+        assert_eq!(blob.char_count(), 3);
+    }
+    
+    #[test]
+    #[cfg(windows)]
+    fn test_windows_name_separators() {
+        let blob = FileBlob::from_data(Path::new(r"project\dist\app.js"), b"console.log(1);\n".to_vec());
+        assert_eq!(blob.name(), "project/dist/app.js");
+        assert!(blob.is_vendored());
+    }
+    
+    #[test]
+    fn test_try_as_utf8() {
+        let blob = FileBlob::from_data(Path::new("hello.txt"), "héllo".as_bytes().to_vec());
+        assert_eq!(blob.try_as_utf8(), Some("héllo"));
+        // The cached validation returns the same borrowed data
+        assert_eq!(blob.try_as_utf8().map(str::as_ptr), Some(blob.data().as_ptr()));
+        
+        let blob = FileBlob::from_data(Path::new("data.bin"), vec![0xff, 0xfe, 0x41]);
+        assert_eq!(blob.try_as_utf8(), None);
+        assert_eq!(blob.try_as_utf8(), None);
+        
+        assert_eq!(truncate_str("héllo", 2), "h");
+        
+        assert_eq!(truncate_str("héllo", 3), "hé");
+        assert_eq!(truncate_str("héllo", 100), "héllo");
+        
+        // Only the prefix is validated
+        let blob = FileBlob::from_data(Path::new("hello.txt"), b"h\xc3\xa9llo \xff".to_vec());
+        assert_eq!(blob.utf8_prefix(2), Some("h"));
+        assert_eq!(blob.utf8_prefix(6), Some("héllo"));
+        assert_eq!(blob.utf8_prefix(100), None);
+        assert_eq!(blob.try_as_utf8(), None);
+        assert_eq!(blob.utf8_prefix(6), Some("héllo"));
+        
+        let blob = FileBlob::from_data(Path::new("hello.txt"), "héllo".as_bytes().to_vec());
+        assert!(blob.try_as_utf8().is_some());
+        assert_eq!(blob.utf8_prefix(2), Some("h"));
+    }
+    
+    #[test]
+    fn test_sloc_by_language() {
+        let rust = Language::find_by_name("Rust").unwrap();
+        let patterns = CommentPatterns::for_language(rust).unwrap();
+        
+        let content = "// Header comment\n\nfn main() { // trailing\n    /* inline */ let x = 1;\n    /*\n     * block\n     */\n    println!(\"{}\", x);\n}\n";
+        let blob = FileBlob::from_data(Path::new("main.rs"), content.as_bytes().to_vec());
+        
+        assert_eq!(blob.sloc(), 8);
+        assert_eq!(blob.sloc_by_language(patterns), Some(4));
+        
+        let python = Language::find_by_name("Python").unwrap();
+        let patterns = CommentPatterns::for_language(python).unwrap();
+        let blob = FileBlob::from_data(Path::new("main.py"), b"# comment\nprint(1)\n".to_vec());
+        assert_eq!(blob.sloc_by_language(patterns), Some(1));
+        
+        let lua = Language::find_by_name("Lua").unwrap();
+        let patterns = CommentPatterns::for_language(lua).unwrap();
+        let content = "--[[\nlocal hidden = 1\n]]\n-- comment\nlocal x = 1\n";
+        let blob = FileBlob::from_data(Path::new("main.lua"), content.as_bytes().to_vec());
+        assert_eq!(blob.sloc_by_language(patterns), Some(1));
+        
+        let julia = Language::find_by_name("Julia").unwrap();
+        let patterns = CommentPatterns::for_language(julia).unwrap();
+        let content = "#=\nx = 1\n=# y = 2\n# comment\n";
+        let blob = FileBlob::from_data(Path::new("main.jl"), content.as_bytes().to_vec());
+        assert_eq!(blob.sloc_by_language(patterns), Some(1));
+        
+        let binary = FileBlob::from_data(Path::new("data.bin"), vec![0, 1, 2]);
+        assert_eq!(binary.sloc_by_language(patterns), None);
+        
+        assert!(CommentPatterns::for_language(Language::find_by_name("JSON").unwrap()).is_none());
+    }
 }
\ No newline at end of file
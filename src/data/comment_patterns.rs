@@ -0,0 +1,207 @@
+//! Comment syntax of common languages.
+//!
+//! This module provides the comment markers used to tell code lines from
+//! comment lines when counting source lines of code.
+
+use std::collections::HashMap;
+
+use crate::language::Language;
+
+lazy_static::lazy_static! {
+    // Comment syntax keyed by language name
+    static ref COMMENT_PATTERNS: HashMap<&'static str, CommentPatterns> = {
+        let c_style = Some(("/*", "*/"));
+        let xml_style = Some(("<!--", "-->"));
+        let ml_style = Some(("(*", "*)"));
+        let lisp_style = Some(("#|", "|#"));
+        
+        let mut patterns = HashMap::new();
+        
+        // `//` and `/* */`
+        for name in [
+            "C", "C++", "C#", "Objective-C", "Objective-C++", "Java", "JavaScript", "TypeScript",
+            "TSX", "Go", "Rust", "Swift", "Kotlin", "Scala", "Dart", "Groovy", "Solidity", "GLSL",
+            "HLSL", "Less", "SCSS", "Protocol Buffer", "Apex", "Verilog", "SystemVerilog", "Cuda",
+            "Processing", "D", "Haxe",
+        ] {
+            patterns.insert(name, CommentPatterns::new(&["//"], c_style));
+        }
+        patterns.insert("Zig", CommentPatterns::new(&["//"], None));
+        patterns.insert("PHP", CommentPatterns::new(&["//", "#"], c_style));
+        patterns.insert("HCL", CommentPatterns::new(&["#", "//"], c_style));
+        patterns.insert("CSS", CommentPatterns::new(&[], c_style));
+        
+        // `#`
+        for name in [
+            "Python", "Shell", "Perl", "R", "YAML", "TOML", "Makefile", "Dockerfile", "CMake",
+            "Elixir", "Crystal", "Tcl", "GraphQL", "Raku", "Starlark", "Awk", "Just",
+        ] {
+            patterns.insert(name, CommentPatterns::new(&["#"], None));
+        }
+        patterns.insert("Ruby", CommentPatterns::new(&["#"], Some(("=begin", "=end"))));
+        patterns.insert("CoffeeScript", CommentPatterns::new(&["#"], Some(("###", "###"))));
+        patterns.insert("Julia", CommentPatterns::new(&["#"], Some(("#=", "=#"))));
+        patterns.insert("Nim", CommentPatterns::new(&["#"], Some(("#[", "]#"))));
+        patterns.insert("PowerShell", CommentPatterns::new(&["#"], Some(("<#", "#>"))));
+        patterns.insert("Nix", CommentPatterns::new(&["#"], c_style));
+        
+        // `--`
+        for name in ["SQL", "PLpgSQL", "PLSQL", "TSQL"] {
+            patterns.insert(name, CommentPatterns::new(&["--"], c_style));
+        }
+        for name in ["Haskell", "Elm", "PureScript"] {
+            patterns.insert(name, CommentPatterns::new(&["--"], Some(("{-", "-}"))));
+        }
+        patterns.insert("Lua", CommentPatterns::new(&["--"], Some(("--[[", "]]"))));
+        patterns.insert("Ada", CommentPatterns::new(&["--"], None));
+        patterns.insert("VHDL", CommentPatterns::new(&["--"], None));
+        
+        // `%`
+        patterns.insert("TeX", CommentPatterns::new(&["%"], None));
+        patterns.insert("Erlang", CommentPatterns::new(&["%"], None));
+        patterns.insert("MATLAB", CommentPatterns::new(&["%"], Some(("%{", "%}"))));
+        patterns.insert("Prolog", CommentPatterns::new(&["%"], c_style));
+        
+        // `;`
+        for name in ["Common Lisp", "Scheme", "Racket"] {
+            patterns.insert(name, CommentPatterns::new(&[";"], lisp_style));
+        }
+        for name in ["Emacs Lisp", "Clojure", "Assembly"] {
+            patterns.insert(name, CommentPatterns::new(&[";"], None));
+        }
+        patterns.insert("INI", CommentPatterns::new(&[";", "#"], None));
+        
+        // `(* *)`
+        patterns.insert("OCaml", CommentPatterns::new(&[], ml_style));
+        patterns.insert("F#", CommentPatterns::new(&["//"], ml_style));
+        patterns.insert("Pascal", CommentPatterns::new(&["//"], ml_style));
+        
+        // `<!-- -->`
+        for name in ["HTML", "XML", "Vue", "Svelte", "Markdown"] {
+            patterns.insert(name, CommentPatterns::new(&[], xml_style));
+        }
+        
+        // Others
+        patterns.insert("Fortran", CommentPatterns::new(&["!"], None));
+        patterns.insert("Fortran Free Form", CommentPatterns::new(&["!"], None));
+        patterns.insert("Visual Basic .NET", CommentPatterns::new(&["'"], None));
+        patterns.insert("Batchfile", CommentPatterns::new(&["::", "REM ", "rem "], None));
+        patterns.insert("Smalltalk", CommentPatterns::new(&[], Some(("\"", "\""))));
+        
+        patterns
+    };
+}
+
+/// Comment syntax of a language
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentPatterns {
+    /// Markers that start a comment running to the end of the line (e.g. `//`)
+    pub single_line: Vec<String>,
+    
+    /// Marker that opens a block comment (e.g. `/*`)
+    pub block_start: Option<String>,
+    
+    /// Marker that closes a block comment (e.g. `*/`)
+    pub block_end: Option<String>,
+}
+
+impl CommentPatterns {
+    /// Create comment patterns from string slices
+    fn new(single_line: &[&str], block: Option<(&str, &str)>) -> Self {
+        Self {
+            single_line: single_line.iter().map(|s| s.to_string()).collect(),
+            block_start: block.map(|(start, _)| start.to_string()),
+            block_end: block.map(|(_, end)| end.to_string()),
+        }
+    }
+    
+    /// Get the comment syntax of a common language
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The language
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&CommentPatterns>` - The comment syntax, if known
+    pub fn for_language(language: &Language) -> Option<&'static CommentPatterns> {
+        COMMENT_PATTERNS.get(language.name.as_str())
+    }
+    
+    /// Check whether a line contains code, tracking block comments across lines
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line to check
+    /// * `in_block` - Whether the line starts inside a block comment; updated for the next line
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the line has anything besides whitespace and comments
+    pub(crate) fn is_code_line(&self, line: &str, in_block: &mut bool) -> bool {
+        let mut rest = line.trim();
+        
+        loop {
+            if *in_block {
+                let end = match &self.block_end {
+                    Some(end) => end,
+                    None => return false,
+                };
+                
+                match rest.find(end.as_str()) {
+                    Some(pos) => {
+                        *in_block = false;
+                        rest = rest[pos + end.len()..].trim_start();
+                    },
+                    None => return false,
+                }
+            }
+            
+            // Block openers can begin with a line marker (Lua `--[[`, Julia `#=`)
+            match &self.block_start {
+                Some(start) if rest.starts_with(start.as_str()) => {
+                    *in_block = true;
+                    rest = &rest[start.len()..];
+                    continue;
+                },
+                _ => (),
+            }
+            
+            return !rest.is_empty() && !self.single_line.iter().any(|marker| rest.starts_with(marker.as_str()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_comment_patterns() {
+        assert!(COMMENT_PATTERNS.len() >= 30);
+        
+        // Every entry must name a known language
+        for name in COMMENT_PATTERNS.keys() {
+            assert!(Language::find_by_name(name).is_some(), "unknown language {}", name);
+        }
+        
+        let haskell = CommentPatterns::for_language(Language::find_by_name("Haskell").unwrap()).unwrap();
+        assert_eq!(haskell.single_line, vec!["--".to_string()]);
+        assert_eq!(haskell.block_start.as_deref(), Some("{-"));
+        assert_eq!(haskell.block_end.as_deref(), Some("-}"));
+        
+        assert!(CommentPatterns::for_language(Language::find_by_name("JSON").unwrap()).is_none());
+    }
+    
+    #[test]
+    fn test_is_code_line() {
+        let html = CommentPatterns::for_language(Language::find_by_name("HTML").unwrap()).unwrap();
+        let mut in_block = false;
+        
+        assert!(!html.is_code_line("<!-- start", &mut in_block));
+        assert!(in_block);
+        assert!(!html.is_code_line("still a comment", &mut in_block));
+        assert!(html.is_code_line("--> <p>text</p>", &mut in_block));
+        assert!(!in_block);
+    }
+}
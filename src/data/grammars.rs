@@ -2,7 +2,12 @@
 //!
 //! This module handles TextMate grammar information for syntax highlighting.
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::language::Language;
+use crate::Result;
 
 /// Get the path to the directory containing language grammar JSON files
 ///
@@ -13,13 +18,118 @@ pub fn path() -> &'static str {
     concat!(env!("CARGO_MANIFEST_DIR"), "/grammars")
 }
 
+/// List the grammar files in the grammars directory
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, PathBuf)>>` - `(language_name, grammar_path)` pairs, sorted by language name
+pub fn list() -> Result<Vec<(String, PathBuf)>> {
+    list_from(Path::new(path()))
+}
+
+/// List the grammar files in a directory
+///
+/// A grammar file belongs to a language when its name without the `.json`
+/// extension matches the language's TextMate scope (e.g. `source.rust`),
+/// name or one of its aliases, compared case-insensitively. The scope
+/// takes precedence over the name, and the name over the aliases.
+///
+/// # Arguments
+///
+/// * `dir` - The directory containing grammar JSON files
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, PathBuf)>>` - `(language_name, grammar_path)` pairs, sorted by language name
+pub fn list_from(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut grammars = HashMap::new();
+    
+    // Check if grammars directory exists
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            grammars.insert(stem.to_lowercase(), path.clone());
+        }
+    }
+    
+    let mut result: Vec<(String, PathBuf)> = Language::all()
+        .iter()
+        .filter_map(|language| {
+            let scope = language.tm_scope.as_deref().filter(|scope| *scope != "none");
+            
+            scope.into_iter()
+                .chain(std::iter::once(language.name.as_str()))
+                .chain(language.aliases.iter().map(String::as_str))
+                .find_map(|key| grammars.get(&key.to_lowercase()))
+                .map(|path| (language.name.clone(), path.clone()))
+        })
+        .collect();
+    result.sort();
+    
+    Ok(result)
+}
+
+/// Find the grammar file of a language
+///
+/// # Arguments
+///
+/// * `language_name` - The language name
+///
+/// # Returns
+///
+/// * `Option<PathBuf>` - The grammar path, if the grammars directory has one for the language
+pub fn find(language_name: &str) -> Option<PathBuf> {
+    let language = Language::find_by_name(language_name)?;
+    
+    list().ok()?
+        .into_iter()
+        .find(|(name, _)| name == &language.name)
+        .map(|(_, path)| path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::tempdir;
     
     #[test]
     fn test_path() {
         let grammar_path = path();
         assert!(!grammar_path.is_empty());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_list_from() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("source.rust.json"), "{}")?;
+        fs::write(dir.path().join("Python.json"), "{}")?;
+        fs::write(dir.path().join("source.rust.plist"), "")?;
+        fs::write(dir.path().join("not-a-language.json"), "{}")?;
+        
+        let grammars = list_from(dir.path())?;
+        let names: Vec<&str> = grammars.iter().map(|(name, _)| name.as_str()).collect();
+        assert!(names.contains(&"Rust"));
+        assert!(names.contains(&"Python"));
+        assert!(names.windows(2).all(|pair| pair[0] <= pair[1]));
+        
+        let rust = grammars.iter().find(|(name, _)| name == "Rust").unwrap();
+        assert_eq!(rust.1, dir.path().join("source.rust.json"));
+        
+        assert!(list_from(&dir.path().join("missing"))?.is_empty());
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_find() {
+        assert!(find("NotALanguage").is_none());
+    }
+}
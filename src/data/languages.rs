@@ -4,12 +4,15 @@
 //! and preparing the necessary indices for fast language lookups.
 
 use std::collections::{HashMap, HashSet};
-use std::sync::Once;
+use std::path::Path;
+use std::sync::{Mutex, Once, OnceLock, PoisonError, RwLock};
 
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use sha2::{Digest, Sha256};
 
 use crate::language::Language;
+use crate::strategy::StrategyType;
 use crate::Result;
 
 // Compile-time inclusion of YAML files
@@ -20,13 +23,125 @@ const POPULAR_YML: &str = include_str!("../../data/popular.yml");
 static INIT: Once = Once::new();
 static mut POPULAR_DATA: Option<Vec<String>> = None;
 
+// The language data in use; replaced by `reload`
+static LANGUAGE_DATA: RwLock<Option<&'static LanguageData>> = RwLock::new(None);
+
+// Every version of the language data loaded so far, by SHA-256 digest of its languages.yml
+static LOADED_DATA: Mutex<Vec<([u8; 32], &'static LanguageData)>> = Mutex::new(Vec::new());
+
+/// Language definitions and the indices used to look them up
+#[derive(Debug)]
+pub struct LanguageData {
+    /// The language definitions, sorted by language ID
+    pub languages: Vec<Language>,
+    
+    /// Lowercase language name to index
+    pub name_index: HashMap<String, usize>,
+    
+    /// Lowercase alias to index
+    pub alias_index: HashMap<String, usize>,
+    
+    /// Lowercase name or alias to index
+    pub language_index: HashMap<String, usize>,
+    
+    /// Language ID to index
+    pub language_id_index: HashMap<usize, usize>,
+    
+    /// Lowercase extension to indices
+    pub extension_index: HashMap<String, Vec<usize>>,
+    
+    /// Interpreter to indices
+    pub interpreter_index: HashMap<String, Vec<usize>>,
+    
+    /// Filename to indices
+    pub filename_index: HashMap<String, Vec<usize>>,
+    
+    /// MIME types mapped to languages, built on first use
+    pub(crate) mime_types: OnceLock<HashMap<String, &'static Language>>,
+    
+    /// Extensions detected without running strategies, built on first use
+    pub(crate) unambiguous_extensions: OnceLock<HashSet<String>>,
+    
+    /// Detection strategies built from these languages, built on first use
+    pub(crate) strategies: OnceLock<Result<Vec<StrategyType>>>,
+    
+    /// The strategies without Xml and Classifier, built on first use
+    pub(crate) fast_strategies: OnceLock<Vec<StrategyType>>,
+}
+
+/// Get the language data in use, loading the embedded languages.yml on first use
+pub(crate) fn current() -> &'static LanguageData {
+    if let Some(data) = *LANGUAGE_DATA.read().unwrap_or_else(PoisonError::into_inner) {
+        return data;
+    }
+    
+    let mut data = LANGUAGE_DATA.write().unwrap_or_else(PoisonError::into_inner);
+    data.get_or_insert_with(|| {
+        let yaml = load_languages_yml().expect("Failed to load languages.yml");
+        load(yaml).expect("Failed to parse languages.yml")
+    })
+}
+
+/// Get the language data for languages.yml content, parsing it only if it
+/// hasn't been loaded before
+///
+/// Lookups hand out `&'static` languages, so loaded data is never freed.
+/// Keeping one copy per distinct file bounds the memory to the number of
+/// versions loaded, however often they are reloaded.
+///
+/// # Arguments
+///
+/// * `languages_yaml` - The content of a languages.yml file
+///
+/// # Returns
+///
+/// * `Result<&LanguageData>` - The languages and their indices
+fn load(languages_yaml: &str) -> Result<&'static LanguageData> {
+    let digest: [u8; 32] = Sha256::digest(languages_yaml.as_bytes()).into();
+    let mut loaded = LOADED_DATA.lock().unwrap_or_else(PoisonError::into_inner);
+    
+    if let Some(&(_, data)) = loaded.iter().find(|(loaded_digest, _)| *loaded_digest == digest) {
+        return Ok(data);
+    }
+    
+    let data: &'static LanguageData = Box::leak(Box::new(parse_language_data(languages_yaml)?));
+    loaded.push((digest, data));
+    Ok(data)
+}
+
+/// Replace the language data with the definitions from a languages.yml file
+///
+/// Languages returned by earlier lookups stay valid. Detection strategies
+/// and other rules built from the languages are rebuilt for the new data,
+/// and the global detection cache is cleared. Reloading a file that was
+/// loaded before reuses its data, so periodic reloads of an unchanged file
+/// don't use more memory.
+///
+/// # Arguments
+///
+/// * `path` - Path to a file in the format of languages.yml
+///
+/// # Returns
+///
+/// * `Result<()>` - Success, or an error if the file can't be read or parsed
+pub fn reload(path: &Path) -> Result<()> {
+    let yaml = std::fs::read_to_string(path)?;
+    let data = load(&yaml)?;
+    
+    *LANGUAGE_DATA.write().unwrap_or_else(PoisonError::into_inner) = Some(data);
+    
+    // Cached results were detected with the old definitions
+    crate::clear_global_detection_cache();
+    Ok(())
+}
+
 /// Load the language data from the embedded languages.yml file (now at compile time)
 fn load_languages_yml() -> Result<&'static str> {
     Ok(LANGUAGES_YML)
 }
 
 /// Get the list of popular language names
-fn get_popular_languages() -> Result<Vec<String>> {
+pub(crate) fn get_popular_languages() -> Result<Vec<String>> {
     unsafe {
         INIT.call_once(|| {
             let popular: Vec<String> = serde_yaml::from_str(POPULAR_YML).expect("Failed to parse popular.yml");
@@ -62,13 +177,44 @@ pub fn load_language_data() -> (
     HashMap<String, Vec<usize>>,
     HashMap<String, Vec<usize>>,
 ) {
-    // Load YAML data
     let languages_yaml = load_languages_yml().expect("Failed to load languages.yml");
-    let popular_languages = get_popular_languages().expect("Failed to load popular.yml");
+    let data = parse_language_data(languages_yaml).expect("Failed to parse languages.yml");
+    
+    (
+        data.languages,
+        data.name_index,
+        data.alias_index,
+        data.language_index,
+        data.language_id_index,
+        data.extension_index,
+        data.interpreter_index,
+        data.filename_index,
+    )
+}
+
+/// Build language definitions and lookup indices from languages.yml content
+///
+/// # Arguments
+///
+/// * `languages_yaml` - The content of a languages.yml file
+///
+/// # Returns
+///
+/// * `Result<LanguageData>` - The languages and their indices
+fn parse_language_data(languages_yaml: &str) -> Result<LanguageData> {
+    let popular_languages = get_popular_languages()?;
     
     // Parse YAML into a map
-    let lang_map: HashMap<String, Value> = serde_yaml::from_str(&languages_yaml)
-        .expect("Failed to parse languages.yml");
+    let lang_map: HashMap<String, Value> = serde_yaml::from_str(languages_yaml)?;
+    
+    // Load in language_id order so that `Language::all()` and the index
+    // lists don't depend on hash map iteration order
+    let mut lang_entries: Vec<(String, Value)> = lang_map.into_iter().collect();
+    lang_entries.sort_by(|(a_name, a_attrs), (b_name, b_attrs)| {
+        let a_id = a_attrs.get("language_id").and_then(Value::as_u64);
+        let b_id = b_attrs.get("language_id").and_then(Value::as_u64);
+        a_id.cmp(&b_id).then_with(|| a_name.cmp(b_name))
+    });
     
     // Create languages and indices
     let mut languages = Vec::new();
@@ -81,139 +227,11 @@ pub fn load_language_data() -> (
     let mut filename_index: HashMap<String, Vec<usize>> = HashMap::new();
     
     // Convert each language entry to a Language struct
-    for (name, attrs) in lang_map {
-        let popular = popular_languages.contains(&name);
-        
-        // Start with default values
-        let mut language = Language {
-            name: name.clone(),
-            fs_name: None,
-            language_type: crate::language::LanguageType::Other,
-            color: None,
-            aliases: Vec::new(),
-            tm_scope: None,
-            ace_mode: None,
-            codemirror_mode: None,
-            codemirror_mime_type: None,
-            wrap: false,
-            extensions: Vec::new(),
-            filenames: Vec::new(),
-            interpreters: Vec::new(),
-            language_id: 0,
-            popular,
-            group_name: None,
-            group: None,
-        };
-        
-        // Fill in values from the YAML
-        if let Value::Mapping(map) = attrs {
-            for (key, value) in map {
-                if let Value::String(key_str) = key {
-                    match key_str.as_str() {
-                        "fs_name" => {
-                            if let Value::String(fs_name) = value {
-                                language.fs_name = Some(fs_name);
-                            }
-                        },
-                        "type" => {
-                            if let Value::String(type_str) = value {
-                                language.language_type = match type_str.as_str() {
-                                    "data" => crate::language::LanguageType::Data,
-                                    "programming" => crate::language::LanguageType::Programming,
-                                    "markup" => crate::language::LanguageType::Markup,
-                                    "prose" => crate::language::LanguageType::Prose,
-                                    _ => crate::language::LanguageType::Other,
-                                };
-                            }
-                        },
-                        "color" => {
-                            if let Value::String(color) = value {
-                                language.color = Some(color);
-                            }
-                        },
-                        "aliases" => {
-                            if let Value::Sequence(aliases) = value {
-                                for alias in aliases {
-                                    if let Value::String(alias_str) = alias {
-                                        language.aliases.push(alias_str);
-                                    }
-                                }
-                            }
-                        },
-                        "tm_scope" => {
-                            if let Value::String(tm_scope) = value {
-                                language.tm_scope = Some(tm_scope);
-                            }
-                        },
-                        "ace_mode" => {
-                            if let Value::String(ace_mode) = value {
-                                language.ace_mode = Some(ace_mode);
-                            }
-                        },
-                        "codemirror_mode" => {
-                            if let Value::String(codemirror_mode) = value {
-                                language.codemirror_mode = Some(codemirror_mode);
-                            }
-                        },
-                        "codemirror_mime_type" => {
-                            if let Value::String(codemirror_mime_type) = value {
-                                language.codemirror_mime_type = Some(codemirror_mime_type);
-                            }
-                        },
-                        "wrap" => {
-                            if let Value::Bool(wrap) = value {
-                                language.wrap = wrap;
-                            }
-                        },
-                        "extensions" => {
-                            if let Value::Sequence(extensions) = value {
-                                for ext in extensions {
-                                    if let Value::String(ext_str) = ext {
-                                        language.extensions.push(ext_str);
-                                    }
-                                }
-                            }
-                        },
-                        "filenames" => {
-                            if let Value::Sequence(filenames) = value {
-                                for filename in filenames {
-                                    if let Value::String(filename_str) = filename {
-                                        language.filenames.push(filename_str);
-                                    }
-                                }
-                            }
-                        },
-                        "interpreters" => {
-                            if let Value::Sequence(interpreters) = value {
-                                for interpreter in interpreters {
-                                    if let Value::String(interpreter_str) = interpreter {
-                                        language.interpreters.push(interpreter_str);
-                                    }
-                                }
-                            }
-                        },
-                        "language_id" => {
-                            if let Value::Number(language_id) = value {
-                                if let Some(id) = language_id.as_u64() {
-                                    language.language_id = id as usize;
-                                }
-                            }
-                        },
-                        "group" => {
-                            if let Value::String(group_name) = value {
-                                language.group_name = Some(group_name);
-                            }
-                        },
-                        _ => {}
-                    }
-                }
-            }
-        }
-        
-        // If no aliases, add default alias
-        if language.aliases.is_empty() {
-            language.aliases.push(language.default_alias());
-        }
+    for (name, attrs) in lang_entries {
+        let popularity_rank = popular_languages.iter()
+            .position(|popular_name| popular_name == &name)
+            .map(|position| position + 1);
+        let language = parse_language(name, attrs, popularity_rank);
         
         // Add to languages and build indices
         let index = languages.len();
@@ -271,7 +289,167 @@ pub fn load_language_data() -> (
         indices.sort();
     }
     
-    (languages, name_index, alias_index, language_index, language_id_index, extension_index, interpreter_index, filename_index)
+    Ok(LanguageData {
+        languages,
+        name_index,
+        alias_index,
+        language_index,
+        language_id_index,
+        extension_index,
+        interpreter_index,
+        filename_index,
+        mime_types: OnceLock::new(),
+        unambiguous_extensions: OnceLock::new(),
+        strategies: OnceLock::new(),
+        fast_strategies: OnceLock::new(),
+    })
+}
+
+/// Build a language from its entry in languages.yml
+///
+/// # Arguments
+///
+/// * `name` - The language name
+/// * `attrs` - The attributes of the entry
+/// * `popularity_rank` - Position in popular.yml, starting at 1, if the language is popular
+///
+/// # Returns
+///
+/// * `Language` - The language; unknown or malformed attributes are ignored
+pub(crate) fn parse_language(name: String, attrs: Value, popularity_rank: Option<usize>) -> Language {
+    // Start with default values
+    let mut language = Language {
+        name,
+        fs_name: None,
+        language_type: crate::language::LanguageType::Other,
+        color: None,
+        aliases: Vec::new(),
+        tm_scope: None,
+        ace_mode: None,
+        codemirror_mode: None,
+        codemirror_mime_type: None,
+        wrap: false,
+        extensions: Vec::new(),
+        filenames: Vec::new(),
+        interpreters: Vec::new(),
+        language_id: 0,
+        popular: popularity_rank.is_some(),
+        popularity_rank,
+        group_name: None,
+        group: None,
+    };
+    
+    // Fill in values from the YAML
+    if let Value::Mapping(map) = attrs {
+        for (key, value) in map {
+            if let Value::String(key_str) = key {
+                match key_str.as_str() {
+                    "fs_name" => {
+                        if let Value::String(fs_name) = value {
+                            language.fs_name = Some(fs_name);
+                        }
+                    },
+                    "type" => {
+                        if let Value::String(type_str) = value {
+                            language.language_type = match type_str.as_str() {
+                                "data" => crate::language::LanguageType::Data,
+                                "programming" => crate::language::LanguageType::Programming,
+                                "markup" => crate::language::LanguageType::Markup,
+                                "prose" => crate::language::LanguageType::Prose,
+                                _ => crate::language::LanguageType::Other,
+                            };
+                        }
+                    },
+                    "color" => {
+                        if let Value::String(color) = value {
+                            language.color = Some(color);
+                        }
+                    },
+                    "aliases" => {
+                        if let Value::Sequence(aliases) = value {
+                            for alias in aliases {
+                                if let Value::String(alias_str) = alias {
+                                    language.aliases.push(alias_str);
+                                }
+                            }
+                        }
+                    },
+                    "tm_scope" => {
+                        if let Value::String(tm_scope) = value {
+                            language.tm_scope = Some(tm_scope);
+                        }
+                    },
+                    "ace_mode" => {
+                        if let Value::String(ace_mode) = value {
+                            language.ace_mode = Some(ace_mode);
+                        }
+                    },
+                    "codemirror_mode" => {
+                        if let Value::String(codemirror_mode) = value {
+                            language.codemirror_mode = Some(codemirror_mode);
+                        }
+                    },
+                    "codemirror_mime_type" => {
+                        if let Value::String(codemirror_mime_type) = value {
+                            language.codemirror_mime_type = Some(codemirror_mime_type);
+                        }
+                    },
+                    "wrap" => {
+                        if let Value::Bool(wrap) = value {
+                            language.wrap = wrap;
+                        }
+                    },
+                    "extensions" => {
+                        if let Value::Sequence(extensions) = value {
+                            for ext in extensions {
+                                if let Value::String(ext_str) = ext {
+                                    language.extensions.push(ext_str);
+                                }
+                            }
+                        }
+                    },
+                    "filenames" => {
+                        if let Value::Sequence(filenames) = value {
+                            for filename in filenames {
+                                if let Value::String(filename_str) = filename {
+                                    language.filenames.push(filename_str);
+                                }
+                            }
+                        }
+                    },
+                    "interpreters" => {
+                        if let Value::Sequence(interpreters) = value {
+                            for interpreter in interpreters {
+                                if let Value::String(interpreter_str) = interpreter {
+                                    language.interpreters.push(interpreter_str);
+                                }
+                            }
+                        }
+                    },
+                    "language_id" => {
+                        if let Value::Number(language_id) = value {
+                            if let Some(id) = language_id.as_u64() {
+                                language.language_id = id as usize;
+                            }
+                        }
+                    },
+                    "group" => {
+                        if let Value::String(group_name) = value {
+                            language.group_name = Some(group_name);
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+    
+    // If no aliases, add default alias
+    if language.aliases.is_empty() {
+        language.aliases.push(language.default_alias());
+    }
+    
+    language
 }
 
 #[cfg(test)]
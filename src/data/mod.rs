@@ -1,3 +1,4 @@
+pub mod comment_patterns;
 pub mod grammars;
 pub mod samples;
 pub mod languages;
\ No newline at end of file
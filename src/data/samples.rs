@@ -32,21 +32,53 @@ pub struct Sample {
     pub extension: Option<String>,
 }
 
+/// Summary of sample coverage across languages
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleStatistics {
+    /// Total number of sample files
+    pub total_samples: usize,
+    
+    /// Number of languages with at least one sample
+    pub languages_with_samples: usize,
+    
+    /// Languages from languages.yml without any samples, sorted by name
+    pub languages_without_samples: Vec<String>,
+    
+    /// Average number of samples per language with samples
+    pub avg_samples_per_language: f64,
+    
+    /// Language with the most samples and its sample count
+    pub max_samples: (String, usize),
+}
+
 /// Load sample data from the samples directory
 ///
 /// # Returns
 ///
 /// * `Result<HashMap<String, Vec<Sample>>>` - Mapping of language names to samples
 pub fn load_samples() -> Result<HashMap<String, Vec<Sample>>> {
+    load_samples_from(Path::new(SAMPLES_ROOT))
+}
+
+/// Load sample data from a samples directory laid out as `<root>/<Language>/<sample>`
+///
+/// # Arguments
+///
+/// * `root` - The samples directory
+///
+/// # Returns
+///
+/// * `Result<HashMap<String, Vec<Sample>>>` - Mapping of language names to samples
+pub fn load_samples_from(root: &Path) -> Result<HashMap<String, Vec<Sample>>> {
     let mut samples = HashMap::new();
     
     // Check if samples directory exists
-    if !Path::new(SAMPLES_ROOT).exists() {
+    if !root.exists() {
         return Ok(samples);
     }
     
     // Iterate through language directories
-    for entry in fs::read_dir(SAMPLES_ROOT)? {
+    for entry in fs::read_dir(root)? {
         let entry = entry?;
         let language_path = entry.path();
         
@@ -205,6 +237,57 @@ pub fn extract_sample_data() -> Result<HashMap<String, HashMap<String, Vec<Strin
     Ok(data)
 }
 
+/// Compute sample coverage statistics
+///
+/// # Returns
+///
+/// * `Result<SampleStatistics>` - Sample counts and the languages lacking samples
+pub fn statistics() -> Result<SampleStatistics> {
+    statistics_from(Path::new(SAMPLES_ROOT))
+}
+
+/// Compute sample coverage statistics for a samples directory laid out as `<root>/<Language>/<sample>`
+///
+/// # Arguments
+///
+/// * `root` - The samples directory
+///
+/// # Returns
+///
+/// * `Result<SampleStatistics>` - Sample counts and the languages lacking samples
+pub fn statistics_from(root: &Path) -> Result<SampleStatistics> {
+    let samples = load_samples_from(root)?;
+    
+    let total_samples = samples.values().map(|samples| samples.len()).sum();
+    let languages_with_samples = samples.len();
+    
+    let mut languages_without_samples: Vec<String> = crate::language::Language::all()
+        .iter()
+        .filter(|language| !samples.contains_key(&language.name))
+        .map(|language| language.name.clone())
+        .collect();
+    languages_without_samples.sort();
+    
+    let avg_samples_per_language = if languages_with_samples > 0 {
+        total_samples as f64 / languages_with_samples as f64
+    } else {
+        0.0
+    };
+    
+    let max_samples = samples.iter()
+        .map(|(language, samples)| (language.clone(), samples.len()))
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .unwrap_or_default();
+    
+    Ok(SampleStatistics {
+        total_samples,
+        languages_with_samples,
+        languages_without_samples,
+        avg_samples_per_language,
+        max_samples,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,4 +349,49 @@ mod tests {
             }
         }
     }
+    
+    #[test]
+    fn test_statistics() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let samples: &[(&str, &[&str])] = &[
+            ("Ruby", &["a.rb", "b.rb", "filenames/Rakefile"]),
+            ("Python", &["a.py", "b.py", "c.py", "d.py", "e.py"]),
+            ("Go", &["main.go"]),
+        ];
+        for (language, files) in samples {
+            for file in *files {
+                let path = dir.path().join(language).join(file);
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(path, "sample\n")?;
+            }
+        }
+        
+        let stats = statistics_from(dir.path())?;
+        assert_eq!(stats.total_samples, 9);
+        assert_eq!(stats.languages_with_samples, 3);
+        assert_eq!(stats.avg_samples_per_language, 3.0);
+        assert_eq!(stats.max_samples, ("Python".to_string(), 5));
+        
+        let languages = crate::language::Language::all();
+        assert_eq!(stats.languages_without_samples.len(), languages.len() - 3);
+        assert!(stats.languages_without_samples.contains(&"Rust".to_string()));
+        assert!(!stats.languages_without_samples.contains(&"Ruby".to_string()));
+        assert!(stats.languages_without_samples.windows(2).all(|pair| pair[0] <= pair[1]));
+        
+        // Ties go to the first language by name
+        fs::write(dir.path().join("Go").join("util.go"), "sample\n")?;
+        fs::write(dir.path().join("Go").join("extra.go"), "sample\n")?;
+        fs::write(dir.path().join("Go").join("more.go"), "sample\n")?;
+        fs::write(dir.path().join("Go").join("last.go"), "sample\n")?;
+        assert_eq!(statistics_from(dir.path())?.max_samples, ("Go".to_string(), 5));
+        
+        // A missing directory has no samples
+        let stats = statistics_from(&dir.path().join("missing"))?;
+        assert_eq!(stats.total_samples, 0);
+        assert_eq!(stats.avg_samples_per_language, 0.0);
+        assert_eq!(stats.max_samples, (String::new(), 0));
+        assert_eq!(stats.languages_without_samples.len(), languages.len());
+        
+        Ok(())
+    }
 }
\ No newline at end of file
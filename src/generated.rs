@@ -3,20 +3,50 @@
 //! This module provides functionality to identify files that are generated
 //! by tools rather than written by humans.
 
+use aho_corasick::AhoCorasick;
 use fancy_regex::Regex;
-use std::path::Path;
+
+/// Where in a path a fixed generated-file pattern has to occur
+#[derive(Debug, Clone, Copy)]
+enum PathMatch {
+    /// Anywhere in the path
+    Anywhere,
+    /// At the end of the path
+    Suffix,
+    /// At the start of a path component
+    ComponentStart,
+}
+
+/// Fixed path patterns of generated files
+const GENERATED_PATH_PATTERNS: &[(&str, PathMatch)] = &[
+    // Xcode
+    (".nib", PathMatch::Suffix),
+    (".xcworkspacedata", PathMatch::Suffix),
+    (".xcuserstate", PathMatch::Suffix),
+    // IntelliJ IDEA
+    (".idea/", PathMatch::ComponentStart),
+    // CocoaPods
+    ("Pods/", PathMatch::ComponentStart),
+    // Carthage
+    ("Carthage/Build/", PathMatch::ComponentStart),
+    // npm
+    ("node_modules/", PathMatch::Anywhere),
+    // Lock files
+    ("composer.lock", PathMatch::Suffix),
+    ("Cargo.lock", PathMatch::Suffix),
+    // GraphQL Relay
+    ("__generated__/", PathMatch::Anywhere),
+    // Protocol Buffers
+    (".pb.go", PathMatch::Suffix),
+];
 
 lazy_static::lazy_static! {
-    // Regular expressions for various generated code patterns
-    static ref XCODE_REGEX: Regex = Regex::new(r"\.(nib|xcworkspacedata|xcuserstate)$").unwrap();
-    static ref IDEA_REGEX: Regex = Regex::new(r"(?:^|\/)\.idea\/").unwrap();
-    static ref COCOAPODS_REGEX: Regex = Regex::new(r"(^Pods|\/Pods)\/").unwrap();
-    static ref CARTHAGE_BUILD_REGEX: Regex = Regex::new(r"(^|\/)Carthage\/Build\/").unwrap();
-    static ref NODE_MODULES_REGEX: Regex = Regex::new(r"node_modules\/").unwrap();
-    static ref COMPOSER_LOCK_REGEX: Regex = Regex::new(r"composer\.lock$").unwrap();
-    static ref CARGO_LOCK_REGEX: Regex = Regex::new(r"Cargo\.lock$").unwrap();
+    // All generated path patterns, matched in a single pass
+    static ref GENERATED_PATHS: AhoCorasick = AhoCorasick::new(
+        GENERATED_PATH_PATTERNS.iter().map(|(pattern, _)| pattern)
+    ).unwrap();
+    
     static ref GENERATED_COMMENT_REGEX: Regex = Regex::new(r"^\s*// (Code )?Generated by\b").unwrap();
-    static ref GENERATED_GRAPHQL_REGEX: Regex = Regex::new(r"__generated__\/").unwrap();
     
     // Minified file patterns
     static ref MINIFIED_EXTENSIONS: Regex = Regex::new(r"(\.|-)min\.(js|css)$").unwrap();
@@ -26,6 +56,28 @@ lazy_static::lazy_static! {
     static ref SOURCE_MAP_CONTENT: Regex = Regex::new(r#"^{"version":3,|^/\*\* Begin line maps\. \*\*/{|^\s*\/\/[@#] sourceMappingURL="#).unwrap();
 }
 
+/// Why a file was detected as generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeneratedReason {
+    /// The path matches a known generated file or directory
+    Path {
+        /// The matching pattern, e.g. `node_modules/`
+        pattern: String,
+    },
+    
+    /// The file is minified JavaScript or CSS
+    Minified,
+    
+    /// The file is a source map
+    SourceMap,
+    
+    /// The first line is a "Generated by" comment
+    GeneratedComment,
+    
+    /// The path is marked `linguist-generated` in `.gitattributes`
+    Attribute,
+}
+
 /// Functionality for detecting generated files
 pub struct Generated;
 
@@ -41,93 +93,65 @@ impl Generated {
     ///
     /// * `bool` - True if the file is detected as generated
     pub fn is_generated(name: &str, data: &[u8]) -> bool {
+        Self::generated_reason(name, data).is_some()
+    }
+    
+    /// Find out why a file is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name/path of the file
+    /// * `data` - The content of the file
+    ///
+    /// # Returns
+    ///
+    /// * `Option<GeneratedReason>` - The first check that matched, or None if the file is not generated
+    pub fn generated_reason(name: &str, data: &[u8]) -> Option<GeneratedReason> {
         // Check filename patterns for known generated files
-        if Self::xcode_file(name) || 
-        Self::intellij_file(name) || 
-        Self::cocoapods(name) || 
-        Self::carthage_build(name) || 
-        Self::node_modules(name) ||
-        Self::composer_lock(name) ||
-        Self::cargo_lock(name) ||
-        Self::generated_graphql_relay(name) {
-         return true;
-        }
-        
-        // Special case for protobuf generated files
-        if name.ends_with(".pb.go") {
-            return true;
-        }
-        
-        // Check file content for generated code patterns
-        if data.is_empty() {
-            return false;
+        if let Some(pattern) = Self::generated_path_pattern(name) {
+            return Some(GeneratedReason::Path { pattern: pattern.to_string() });
         }
         
         // Check file content for generated code patterns
         if data.is_empty() {
-            return false;
+            return None;
         }
         
         // Check for minified files
         if Self::minified_js_or_css(name) && Self::is_minified_content(data) {
-            return true;
+            return Some(GeneratedReason::Minified);
         }
         
         // Check for source maps
         if Self::is_source_map(name, data) {
-            return true;
+            return Some(GeneratedReason::SourceMap);
         }
         
         // Check first line for common "Generated by..." comments
         if let Ok(content) = std::str::from_utf8(data) {
             if let Some(first_line) = content.lines().next() {
                 if GENERATED_COMMENT_REGEX.is_match(first_line).unwrap_or(false) {
-                    return true;
+                    return Some(GeneratedReason::GeneratedComment);
                 }
             }
         }
         
-        false
-    }
-    
-    /// Check if the file is an Xcode file
-    fn xcode_file(name: &str) -> bool {
-        XCODE_REGEX.is_match(name).unwrap_or(false)
-    }
-    
-    /// Check if the file is in an IntelliJ IDEA project directory
-    fn intellij_file(name: &str) -> bool {
-        IDEA_REGEX.is_match(name).unwrap_or(false)
-    }
-    
-    /// Check if the file is part of Pods directory
-    fn cocoapods(name: &str) -> bool {
-        COCOAPODS_REGEX.is_match(name).unwrap_or(false)
-    }
-    
-    /// Check if the file is part of Carthage/Build directory
-    fn carthage_build(name: &str) -> bool {
-        CARTHAGE_BUILD_REGEX.is_match(name).unwrap_or(false)
-    }
-    
-    /// Check if the file is part of node_modules
-    fn node_modules(name: &str) -> bool {
-        NODE_MODULES_REGEX.is_match(name).unwrap_or(false)
+        None
     }
     
-    /// Check if the file is a composer.lock file
-    fn composer_lock(name: &str) -> bool {
-        COMPOSER_LOCK_REGEX.is_match(name).unwrap_or(false)
-    }
-    
-    /// Check if the file is a Cargo.lock file
-    fn cargo_lock(name: &str) -> bool {
-        CARGO_LOCK_REGEX.is_match(name).unwrap_or(false)
-    }
-    
-    /// Check if the file is a generated GraphQL Relay file
-    fn generated_graphql_relay(name: &str) -> bool {
-        GENERATED_GRAPHQL_REGEX.is_match(name).unwrap_or(false)
+    /// Find the known generated file or directory pattern a path matches
+    ///
+    /// All patterns are found in one Aho-Corasick pass over the path, then
+    /// filtered by where each pattern is allowed to occur.
+    fn generated_path_pattern(name: &str) -> Option<&'static str> {
+        GENERATED_PATHS.find_overlapping_iter(name)
+            .map(|m| (m, GENERATED_PATH_PATTERNS[m.pattern().as_usize()]))
+            .find(|(m, (_, position))| match position {
+                PathMatch::Anywhere => true,
+                PathMatch::Suffix => m.end() == name.len(),
+                PathMatch::ComponentStart => m.start() == 0 || name.as_bytes()[m.start() - 1] == b'/',
+            })
+            .map(|(_, (pattern, _))| pattern)
     }
     
     /// Check if the file has a minified extension
@@ -186,24 +210,40 @@ mod tests {
     
     #[test]
     fn test_xcode_detection() {
-        assert!(Generated::xcode_file("project.xcworkspacedata"));
-        assert!(Generated::xcode_file("project.xcuserstate"));
-        assert!(Generated::xcode_file("MyView.nib"));
-        assert!(!Generated::xcode_file("MyCode.swift"));
+        assert!(Generated::generated_path_pattern("project.xcworkspacedata").is_some());
+        assert!(Generated::generated_path_pattern("project.xcuserstate").is_some());
+        assert!(Generated::generated_path_pattern("MyView.nib").is_some());
+        assert!(Generated::generated_path_pattern("MyCode.swift").is_none());
     }
     
     #[test]
     fn test_intellij_detection() {
-        assert!(Generated::intellij_file(".idea/workspace.xml"));
-        assert!(Generated::intellij_file("project/.idea/misc.xml"));
-        assert!(!Generated::intellij_file("idea_file.txt"));
+        assert!(Generated::generated_path_pattern(".idea/workspace.xml").is_some());
+        assert!(Generated::generated_path_pattern("project/.idea/misc.xml").is_some());
+        assert!(Generated::generated_path_pattern("idea_file.txt").is_none());
     }
     
     #[test]
     fn test_node_modules_detection() {
-        assert!(Generated::node_modules("node_modules/lodash/index.js"));
-        assert!(Generated::node_modules("project/node_modules/react/index.js"));
-        assert!(!Generated::node_modules("src/components/node_module_like.js"));
+        assert!(Generated::generated_path_pattern("node_modules/lodash/index.js").is_some());
+        assert!(Generated::generated_path_pattern("project/node_modules/react/index.js").is_some());
+        assert!(Generated::generated_path_pattern("src/components/node_module_like.js").is_none());
+    }
+    
+    #[test]
+    fn test_generated_path_patterns() {
+        assert!(Generated::generated_path_pattern("Pods/Alamofire/Source.swift").is_some());
+        assert!(Generated::generated_path_pattern("ios/Pods/Alamofire/Source.swift").is_some());
+        assert!(Generated::generated_path_pattern("MyPods/Source.swift").is_none());
+        
+        assert!(Generated::generated_path_pattern("Carthage/Build/iOS/Lib.framework").is_some());
+        assert!(Generated::generated_path_pattern("src/__generated__/Query.graphql.js").is_some());
+        
+        assert!(Generated::generated_path_pattern("Cargo.lock").is_some());
+        assert!(Generated::generated_path_pattern("php/composer.lock").is_some());
+        assert!(Generated::generated_path_pattern("api/message.pb.go").is_some());
+        assert!(Generated::generated_path_pattern("Cargo.lock.bak").is_none());
+        assert!(Generated::generated_path_pattern("nibbles.rs").is_none());
     }
     
     #[test]
@@ -240,4 +280,18 @@ mod tests {
         let normal_code = "// This is a regular comment\nfunction main() {}";
         assert!(!Generated::is_generated("normal.js", normal_code.as_bytes()));
     }
+    
+    #[test]
+    fn test_generated_reason() {
+        assert_eq!(
+            Generated::generated_reason("web/node_modules/react/index.js", b"module.exports = {};"),
+            Some(GeneratedReason::Path { pattern: "node_modules/".to_string() })
+        );
+        assert_eq!(Generated::generated_reason("app.js.map", b"{}"), Some(GeneratedReason::SourceMap));
+        assert_eq!(
+            Generated::generated_reason("script.js", b"// Generated by CoffeeScript 1.12.7\nvar x = 5;"),
+            Some(GeneratedReason::GeneratedComment)
+        );
+        assert_eq!(Generated::generated_reason("main.rs", b"fn main() {}"), None);
+    }
 }
\ No newline at end of file
@@ -5,11 +5,14 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::Arc;
 use fancy_regex::Regex;
+use serde_yaml::Value;
 
 use crate::blob::BlobHelper;
 use crate::language::Language;
 use crate::strategy::Strategy;
+use crate::{Error, Result};
 
 // Maximum bytes to consider for heuristic analysis
 const HEURISTICS_CONSIDER_BYTES: usize = 50 * 1024;
@@ -53,17 +56,17 @@ struct Disambiguation {
 }
 
 impl Disambiguation {
-    /// Check if this disambiguation applies to the given file
-    fn matches_extension(&self, filename: &str) -> bool {
-        let path = Path::new(filename.to_lowercase().as_str());
-        
-        for ext in &self.extensions {
-            if filename.to_lowercase().ends_with(ext) {
-                return true;
-            }
-        }
-        
-        false
+    /// Check if this disambiguation applies to the given extension
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - A simple or compound extension, e.g. `.erb` or `.html.erb`
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the extension is one of this rule's extensions
+    fn matches_extension(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(extension))
     }
     
     /// Apply the disambiguation rules to the file content
@@ -88,18 +91,28 @@ impl Disambiguation {
     }
 }
 
-lazy_static::lazy_static! {
-    static ref DISAMBIGUATIONS: Vec<Disambiguation> = {
-        // Manually define disambiguation rules
+/// Heuristics language detection strategy
+#[derive(Debug, Clone)]
+pub struct Heuristics {
+    /// Compiled disambiguation rules, shared between clones
+    disambiguations: Arc<Vec<Disambiguation>>,
+}
+
+impl Heuristics {
+    /// Compile the bundled disambiguation rules
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Heuristics>` - The strategy, or an error if a pattern fails to compile
+    pub fn new_from_bundled() -> Result<Heuristics> {
         // These are based on the rules in heuristics.yml
-        
         let mut disambiguations = Vec::new();
         
         // C/C++ Header disambiguation
-        let mut cpp_extensions = vec![".h".to_string()];
+        let cpp_extensions = vec![".h".to_string()];
         
-        let cpp_rule = Rule::Pattern(Regex::new(r#"^\s*#\s*include <(cstdint|string|vector|map|list|array|bitset|queue|stack|forward_list|unordered_map|unordered_set|(i|o|io)stream)>"#).unwrap());
-        let objective_c_rule = Rule::Pattern(Regex::new(r#"^\s*(@(interface|class|protocol|property|end|synchronised|selector|implementation)\b|#import\s+.+\.h[">])"#).unwrap());
+        let cpp_rule = Rule::Pattern(Regex::new(r#"^\s*#\s*include <(cstdint|string|vector|map|list|array|bitset|queue|stack|forward_list|unordered_map|unordered_set|(i|o|io)stream)>"#)?);
+        let objective_c_rule = Rule::Pattern(Regex::new(r#"^\s*(@(interface|class|protocol|property|end|synchronised|selector|implementation)\b|#import\s+.+\.h[">])"#)?);
         
         let cpp_langs = Language::find_by_name("C++")
             .map(|lang| vec![lang.clone()])
@@ -115,7 +128,7 @@ lazy_static::lazy_static! {
             extensions: cpp_extensions,
             rules: vec![
                 (objective_c_rule, objc_langs),
-                (cpp_rule, cpp_langs.clone()),
+                (cpp_rule, cpp_langs),
                 (Rule::AlwaysMatch, c_langs),
             ],
         });
@@ -123,9 +136,11 @@ lazy_static::lazy_static! {
         // JavaScript/JSX disambiguation
         let js_extensions = vec![".js".to_string()];
         
-        let jsx_rule = Rule::Pattern(Regex::new(r"import\s+React|\bReact\.|<[A-Z][A-Za-z]+>|<\/[A-Z][A-Za-z]+>|<[A-Z][A-Za-z]+\s").unwrap());
+        let jsx_rule = Rule::Pattern(Regex::new(r"import\s+React|\bReact\.|<[A-Z][A-Za-z]+>|<\/[A-Z][A-Za-z]+>|<[A-Z][A-Za-z]+\s")?);
         
-        let js_langs = vec![Language::find_by_name("JavaScript").unwrap().clone()];
+        let javascript = Language::find_by_name("JavaScript")
+            .ok_or_else(|| Error::UnknownLanguage("JavaScript".to_string()))?;
+        let js_langs = vec![javascript.clone()];
         let jsx_langs = if let Some(jsx) = Language::find_by_name("JSX") {
             vec![jsx.clone()]
         } else {
@@ -142,13 +157,163 @@ lazy_static::lazy_static! {
         
         // Add more disambiguations here...
         
-        disambiguations
-    };
+        Ok(Heuristics {
+            disambiguations: Arc::new(disambiguations),
+        })
+    }
+    
+    /// Get the file extensions that have a disambiguation rule
+    ///
+    /// # Returns
+    ///
+    /// * `HashSet<String>` - Lowercase extensions, including the leading dot
+    pub fn coverage(&self) -> HashSet<String> {
+        self.disambiguations.iter()
+            .flat_map(|disambiguation| disambiguation.extensions.iter())
+            .map(|ext| ext.to_lowercase())
+            .collect()
+    }
 }
 
-/// Heuristics language detection strategy
-#[derive(Debug, Clone)]
-pub struct Heuristics;
+/// A problem found in a heuristics.yml file by `validate_yaml`
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum HeuristicsValidationError {
+    /// A pattern does not compile with `fancy_regex`
+    #[error("{location}: invalid pattern {pattern:?}: {message}")]
+    InvalidPattern { location: String, pattern: String, message: String },
+    
+    /// A rule refers to a named pattern that is not defined
+    #[error("{location}: unknown named pattern {name:?}")]
+    UnknownNamedPattern { location: String, name: String },
+    
+    /// A rule refers to a language that does not exist
+    #[error("{location}: unknown language {name:?}")]
+    UnknownLanguage { location: String, name: String },
+    
+    /// A rule without conditions is followed by rules that can never be reached
+    #[error("{location}: rule always matches but is not the last rule")]
+    UnreachableRules { location: String },
+    
+    /// An extension is empty or doesn't start with `.`
+    #[error("{location}: invalid extension {extension:?}")]
+    InvalidExtension { location: String, extension: String },
+}
+
+/// Validate a heuristics.yml file in GitHub Linguist's format
+///
+/// # Arguments
+///
+/// * `path` - Path to the heuristics.yml file
+///
+/// # Returns
+///
+/// * `Result<Vec<HeuristicsValidationError>>` - The problems found, empty if the file is valid
+pub fn validate_yaml(path: &Path) -> Result<Vec<HeuristicsValidationError>> {
+    let content = std::fs::read_to_string(path)?;
+    let yaml: Value = serde_yaml::from_str(&content)?;
+    let mut errors = Vec::new();
+    
+    let named_patterns = yaml.get("named_patterns").and_then(Value::as_mapping);
+    for (name, patterns) in named_patterns.into_iter().flatten() {
+        let location = format!("named_patterns.{}", name.as_str().unwrap_or_default());
+        validate_patterns(patterns, &location, &mut errors);
+    }
+    
+    let disambiguations = yaml.get("disambiguations")
+        .and_then(Value::as_sequence)
+        .ok_or_else(|| Error::Other("heuristics file has no disambiguations list".to_string()))?;
+    
+    for (i, disambiguation) in disambiguations.iter().enumerate() {
+        let location = format!("disambiguations[{}]", i);
+        
+        for ext in disambiguation.get("extensions").and_then(Value::as_sequence).into_iter().flatten() {
+            let extension = ext.as_str().unwrap_or_default();
+            if extension.len() < 2 || !extension.starts_with('.') {
+                errors.push(HeuristicsValidationError::InvalidExtension {
+                    location: format!("{}.extensions", location),
+                    extension: extension.to_string(),
+                });
+            }
+        }
+        
+        let rules = disambiguation.get("rules").and_then(Value::as_sequence).map(Vec::as_slice).unwrap_or_default();
+        for (j, rule) in rules.iter().enumerate() {
+            let location = format!("{}.rules[{}]", location, j);
+            
+            for name in string_list(rule.get("language")) {
+                if Language::find_by_name(name).is_none() {
+                    errors.push(HeuristicsValidationError::UnknownLanguage {
+                        location: location.clone(),
+                        name: name.to_string(),
+                    });
+                }
+            }
+            
+            if is_always_match(rule) && j + 1 < rules.len() {
+                errors.push(HeuristicsValidationError::UnreachableRules { location: location.clone() });
+            }
+            
+            validate_conditions(rule, &location, named_patterns, &mut errors);
+        }
+    }
+    
+    Ok(errors)
+}
+
+/// Get a string or a list of strings from a YAML value
+fn string_list(value: Option<&Value>) -> Vec<&str> {
+    match value {
+        Some(Value::String(s)) => vec![s.as_str()],
+        Some(Value::Sequence(values)) => values.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Check whether a rule has no conditions and therefore always matches
+fn is_always_match(rule: &Value) -> bool {
+    ["pattern", "negative_pattern", "named_pattern", "and"].iter()
+        .all(|key| rule.get(key).is_none())
+}
+
+/// Check that every pattern in a string or list of strings compiles
+fn validate_patterns(patterns: &Value, location: &str, errors: &mut Vec<HeuristicsValidationError>) {
+    for pattern in string_list(Some(patterns)) {
+        if let Err(err) = Regex::new(pattern) {
+            errors.push(HeuristicsValidationError::InvalidPattern {
+                location: location.to_string(),
+                pattern: pattern.to_string(),
+                message: err.to_string(),
+            });
+        }
+    }
+}
+
+/// Check the patterns and named pattern references of a rule and its `and` sub-rules
+fn validate_conditions(
+    rule: &Value,
+    location: &str,
+    named_patterns: Option<&serde_yaml::Mapping>,
+    errors: &mut Vec<HeuristicsValidationError>,
+) {
+    for key in ["pattern", "negative_pattern"] {
+        if let Some(patterns) = rule.get(key) {
+            validate_patterns(patterns, location, errors);
+        }
+    }
+    
+    for name in string_list(rule.get("named_pattern")) {
+        if !named_patterns.is_some_and(|named| named.contains_key(name)) {
+            errors.push(HeuristicsValidationError::UnknownNamedPattern {
+                location: location.to_string(),
+                name: name.to_string(),
+            });
+        }
+    }
+    
+    for (k, sub_rule) in rule.get("and").and_then(Value::as_sequence).into_iter().flatten().enumerate() {
+        validate_conditions(sub_rule, &format!("{}.and[{}]", location, k), named_patterns, errors);
+    }
+}
 
 impl Strategy for Heuristics {
     fn call<B: BlobHelper + ?Sized>(&self, blob: &B, candidates: &[Language]) -> Vec<Language> {
@@ -157,23 +322,21 @@ impl Strategy for Heuristics {
             return Vec::new();
         }
         
-        // Get the data for analysis, limited to a reasonable size
-        let data_bytes = blob.data();
-        let consider_bytes = std::cmp::min(data_bytes.len(), HEURISTICS_CONSIDER_BYTES);
-        let data_slice = &data_bytes[..consider_bytes];
-        
-        // Convert to string for pattern matching
-        let content = match std::str::from_utf8(data_slice) {
-            Ok(s) => s,
-            Err(_) => return Vec::new(), // Binary content
+        // Get the content for pattern matching, limited to a reasonable size
+        let content = match blob.utf8_prefix(HEURISTICS_CONSIDER_BYTES) {
+            Some(s) => s,
+            None => return Vec::new(), // Not UTF-8 text
         };
         
-        // Find a disambiguation that matches the file extension
-        for disambiguation in DISAMBIGUATIONS.iter() {
-            if disambiguation.matches_extension(blob.name()) {
-                let result = disambiguation.disambiguate(content, candidates);
-                if !result.is_empty() {
-                    return result;
+        // Find a disambiguation that matches the file extension, trying
+        // compound extensions (`.html.erb`) before simple ones (`.erb`)
+        for extension in blob.extensions() {
+            for disambiguation in self.disambiguations.iter() {
+                if disambiguation.matches_extension(&extension) {
+                    let result = disambiguation.disambiguate(content, candidates);
+                    if !result.is_empty() {
+                        return result;
+                    }
                 }
             }
         }
@@ -203,7 +366,7 @@ mod tests {
         }
         
         let blob = FileBlob::new(&cpp_path)?;
-        let strategy = Heuristics;
+        let strategy = Heuristics::new_from_bundled()?;
         
         let languages = strategy.call(&blob, &[]);
         assert!(!languages.is_empty());
@@ -236,7 +399,7 @@ mod tests {
         }
         
         let blob = FileBlob::new(&objc_path)?;
-        let strategy = Heuristics;
+        let strategy = Heuristics::new_from_bundled()?;
         
         let languages = strategy.call(&blob, &[]);
         assert!(!languages.is_empty());
@@ -245,6 +408,24 @@ mod tests {
         Ok(())
     }
     
+    #[test]
+    fn test_invalid_utf8_after_prefix() -> crate::Result<()> {
+        // Only the considered prefix has to be valid UTF-8
+        let mut content = b"#import <UIKit/UIKit.h>\n@interface MyView : UIView\n@end\n".to_vec();
+        content.resize(HEURISTICS_CONSIDER_BYTES + 10, b'\n');
+        content.extend_from_slice(b"// caf\xe9\n");
+        
+        let blob = FileBlob::from_data(Path::new("view.h"), content);
+        assert!(blob.try_as_utf8().is_none());
+        
+        let strategy = Heuristics::new_from_bundled()?;
+        let languages = strategy.call(&blob, &[]);
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].name, "Objective-C");
+        
+        Ok(())
+    }
+    
     #[test]
     fn test_jsx_heuristic() -> crate::Result<()> {
         let dir = tempdir()?;
@@ -262,7 +443,7 @@ mod tests {
         }
         
         let blob = FileBlob::new(&jsx_path)?;
-        let strategy = Heuristics;
+        let strategy = Heuristics::new_from_bundled()?;
         
         let languages = strategy.call(&blob, &[]);
         assert!(!languages.is_empty());
@@ -283,6 +464,57 @@ mod tests {
         Ok(())
     }
     
+    #[test]
+    fn test_validate_yaml() -> crate::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("heuristics.yml");
+        std::fs::write(&path, r#"
+disambiguations:
+- extensions: ['.h', 'm']
+  rules:
+  - language: Objective-C
+    named_pattern: objectivec
+  - language: Not A Language
+    pattern: '(unclosed'
+  - language: C
+  - language: C++
+    and:
+    - negative_pattern: '^\s*#'
+    - named_pattern: missing
+named_patterns:
+  objectivec: '^\s*@interface\b'
+"#)?;
+        
+        let errors = validate_yaml(&path)?;
+        assert_eq!(errors.len(), 5, "{:?}", errors);
+        assert!(errors.contains(&HeuristicsValidationError::InvalidExtension {
+            location: "disambiguations[0].extensions".to_string(),
+            extension: "m".to_string(),
+        }));
+        assert!(errors.contains(&HeuristicsValidationError::UnknownLanguage {
+            location: "disambiguations[0].rules[1]".to_string(),
+            name: "Not A Language".to_string(),
+        }));
+        assert!(errors.iter().any(|err| matches!(err, HeuristicsValidationError::InvalidPattern { pattern, .. } if pattern == "(unclosed")));
+        assert!(errors.contains(&HeuristicsValidationError::UnreachableRules {
+            location: "disambiguations[0].rules[2]".to_string(),
+        }));
+        assert!(errors.contains(&HeuristicsValidationError::UnknownNamedPattern {
+            location: "disambiguations[0].rules[3].and[1]".to_string(),
+            name: "missing".to_string(),
+        }));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_coverage() {
+        let coverage = Heuristics::new_from_bundled().unwrap().coverage();
+        assert!(coverage.contains(".h"));
+        assert!(coverage.contains(".js"));
+        assert!(!coverage.contains(".rs"));
+    }
+    
     #[test]
     fn test_heuristics_with_candidates() -> crate::Result<()> {
         let dir = tempdir()?;
@@ -295,7 +527,7 @@ mod tests {
         }
         
         let blob = FileBlob::new(&cpp_path)?;
-        let strategy = Heuristics;
+        let strategy = Heuristics::new_from_bundled()?;
         
         // With C and C++ in candidates
         let c = Language::find_by_name("C").unwrap();
@@ -311,4 +543,25 @@ mod tests {
         
         Ok(())
     }
+    
+    #[test]
+    fn test_compound_extension_priority() {
+        let always = |name: &str| (Rule::AlwaysMatch, vec![Language::find_by_name(name).unwrap().clone()]);
+        let strategy = Heuristics {
+            disambiguations: Arc::new(vec![
+                Disambiguation { extensions: vec![".erb".to_string()], rules: vec![always("Ruby")] },
+                Disambiguation { extensions: vec![".html.erb".to_string()], rules: vec![always("HTML+ERB")] },
+            ]),
+        };
+        
+        let detect = |name: &str| {
+            let blob = FileBlob::from_data(name, b"<%= title %>\n".to_vec());
+            strategy.call(&blob, &[]).into_iter().map(|lang| lang.name).collect::<Vec<_>>()
+        };
+        assert_eq!(detect("index.html.erb"), ["HTML+ERB"]);
+        assert_eq!(detect("INDEX.HTML.ERB"), ["HTML+ERB"]);
+        assert_eq!(detect("helper.erb"), ["Ruby"]);
+        assert_eq!(detect("index.js.erb"), ["Ruby"]);
+        assert!(detect("erb").is_empty());
+    }
 }
\ No newline at end of file
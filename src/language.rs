@@ -4,26 +4,18 @@
 //! looking up languages by name, extension, or filename.
 
 use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::sync::Once;
 
 use serde::{Deserialize, Serialize};
 
 use crate::data::languages;
-use crate::Result;
-
-static INIT: Once = Once::new();
-static mut LANGUAGES: Option<Vec<Language>> = None;
-static mut LANGUAGE_INDEX: Option<HashMap<String, usize>> = None;
-static mut NAME_INDEX: Option<HashMap<String, usize>> = None;
-static mut ALIAS_INDEX: Option<HashMap<String, usize>> = None;
-static mut LANGUAGE_ID_INDEX: Option<HashMap<usize, usize>> = None;
-static mut EXTENSION_INDEX: Option<HashMap<String, Vec<usize>>> = None;
-static mut INTERPRETER_INDEX: Option<HashMap<String, Vec<usize>>> = None;
-static mut FILENAME_INDEX: Option<HashMap<String, Vec<usize>>> = None;
+use crate::{Error, Result};
 
 /// Language type enumerations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LanguageType {
     /// Data languages (JSON, YAML, etc.)
     Data,
@@ -43,52 +35,82 @@ impl Default for LanguageType {
     }
 }
 
+/// Which optional fields of a language definition are populated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LanguageFeatures {
+    /// A color is assigned
+    pub has_color: bool,
+    /// A TextMate scope is assigned
+    pub has_tm_scope: bool,
+    /// An Ace editor mode is assigned
+    pub has_ace_mode: bool,
+    /// A CodeMirror mode is assigned
+    pub has_codemirror_mode: bool,
+    /// At least one extension is listed
+    pub has_extensions: bool,
+    /// At least one interpreter is listed
+    pub has_interpreters: bool,
+    /// At least one filename is listed
+    pub has_filenames: bool,
+    /// The language is listed in popular.yml
+    pub is_popular: bool,
+}
+
 /// Represents a programming or markup language.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+///
+/// Deserializes from a language name or alias, e.g. `"language": "Python"`.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", rename_all = "snake_case")]
 pub struct Language {
     /// The human-readable name of the language
     pub name: String,
     
     /// The name used in filesystem paths
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub fs_name: Option<String>,
     
     /// The type of language
-    #[serde(default)]
+    #[serde(default, rename = "type")]
     pub language_type: LanguageType,
     
     /// The color associated with the language (hex code)
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
     
     /// Alternate names or aliases for the language
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub aliases: Vec<String>,
     
     /// TextMate scope for syntax highlighting
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tm_scope: Option<String>,
     
     /// Ace editor mode
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ace_mode: Option<String>,
     
     /// CodeMirror mode
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub codemirror_mode: Option<String>,
     
     /// CodeMirror MIME type
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub codemirror_mime_type: Option<String>,
     
     /// Whether to wrap text when displaying
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub wrap: bool,
     
     /// File extensions associated with the language
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extensions: Vec<String>,
     
     /// Filenames associated with the language
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub filenames: Vec<String>,
     
     /// Interpreters associated with the language
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub interpreters: Vec<String>,
     
     /// Unique identifier for the language
@@ -98,7 +120,12 @@ pub struct Language {
     #[serde(default)]
     pub popular: bool,
     
+    /// Position of the language in popular.yml, starting at 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub popularity_rank: Option<usize>,
+    
     /// The parent language group name
+    #[serde(rename = "group", skip_serializing_if = "Option::is_none")]
     pub group_name: Option<String>,
     
     /// Cached reference to the group language
@@ -107,30 +134,40 @@ pub struct Language {
 }
 
 impl Language {
-    /// Initialize the language data.
-    fn init() {
-        INIT.call_once(|| {
-            unsafe {
-                // Add a mutex or other synchronization here
-                let (langs, name_idx, alias_idx, lang_idx, lang_id_idx, ext_idx, interp_idx, file_idx) = 
-                    languages::load_language_data();
-                
-                LANGUAGES = Some(langs);
-                LANGUAGE_INDEX = Some(lang_idx);
-                NAME_INDEX = Some(name_idx);
-                ALIAS_INDEX = Some(alias_idx);
-                LANGUAGE_ID_INDEX = Some(lang_id_idx);
-                EXTENSION_INDEX = Some(ext_idx);
-                INTERPRETER_INDEX = Some(interp_idx);
-                FILENAME_INDEX = Some(file_idx);
-            }
-        });
-    }
-
-    /// Get a reference to all known languages.
+    /// Get a reference to all known languages, sorted by language ID.
     pub fn all() -> &'static [Language] {
-        Self::init();
-        unsafe { LANGUAGES.as_ref().unwrap() }
+        &languages::current().languages
+    }
+    
+    /// Get all known languages as JSON, for external tools.
+    ///
+    /// Each language is an object with the keys of GitHub's `languages.yml`
+    /// plus `name`. Every key is present, with `null` for absent values.
+    ///
+    /// # Returns
+    ///
+    /// * `serde_json::Value` - An array of language objects, sorted by language ID
+    pub fn all_as_json() -> serde_json::Value {
+        Self::all()
+            .iter()
+            .map(|language| serde_json::json!({
+                "name": language.name,
+                "fs_name": language.fs_name,
+                "type": language.language_type,
+                "color": language.color,
+                "aliases": language.aliases,
+                "tm_scope": language.tm_scope,
+                "ace_mode": language.ace_mode,
+                "codemirror_mode": language.codemirror_mode,
+                "codemirror_mime_type": language.codemirror_mime_type,
+                "wrap": language.wrap,
+                "extensions": language.extensions,
+                "filenames": language.filenames,
+                "interpreters": language.interpreters,
+                "language_id": language.language_id,
+                "group": language.group_name,
+            }))
+            .collect()
     }
     
     /// Look up a language by name.
@@ -143,25 +180,8 @@ impl Language {
     ///
     /// * `Option<&Language>` - The language if found, None otherwise
     pub fn find_by_name(name: &str) -> Option<&'static Language> {
-        Self::init();
-        
-        let name = name.to_lowercase();
-        
-        unsafe {
-            if let Some(idx) = NAME_INDEX.as_ref().unwrap().get(&name) {
-                return Some(&LANGUAGES.as_ref().unwrap()[*idx]);
-            }
-            
-            // Try looking up by the first part of a comma-separated name
-            if name.contains(',') {
-                let first_part = name.split(',').next().unwrap().trim().to_lowercase();
-                if let Some(idx) = NAME_INDEX.as_ref().unwrap().get(&first_part) {
-                    return Some(&LANGUAGES.as_ref().unwrap()[*idx]);
-                }
-            }
-            
-            None
-        }
+        let data = languages::current();
+        Self::split_and_lookup(name, &data.name_index).map(|idx| &data.languages[idx])
     }
     
     /// Look up a language by alias.
@@ -174,25 +194,29 @@ impl Language {
     ///
     /// * `Option<&Language>` - The language if found, None otherwise
     pub fn find_by_alias(alias: &str) -> Option<&'static Language> {
-        Self::init();
-        
-        let alias = alias.to_lowercase();
-        
-        unsafe {
-            if let Some(idx) = ALIAS_INDEX.as_ref().unwrap().get(&alias) {
-                return Some(&LANGUAGES.as_ref().unwrap()[*idx]);
-            }
-            
-            // Try looking up by the first part of a comma-separated alias
-            if alias.contains(',') {
-                let first_part = alias.split(',').next().unwrap().trim().to_lowercase();
-                if let Some(idx) = ALIAS_INDEX.as_ref().unwrap().get(&first_part) {
-                    return Some(&LANGUAGES.as_ref().unwrap()[*idx]);
-                }
-            }
-            
-            None
+        let data = languages::current();
+        Self::split_and_lookup(alias, &data.alias_index).map(|idx| &data.languages[idx])
+    }
+    
+    /// Look up a key in a lowercase index, falling back to the parts of a comma-separated list
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The key, e.g. `Ruby` or `Ruby, version 3`
+    /// * `index` - The index to look the key up in
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The index entry of the whole key, or else of the first part that has one
+    fn split_and_lookup(s: &str, index: &HashMap<String, usize>) -> Option<usize> {
+        let key = s.to_lowercase();
+        if let Some(idx) = index.get(&key) {
+            return Some(*idx);
         }
+        
+        key.split(',')
+            .map(str::trim)
+            .find_map(|part| index.get(part).copied())
     }
     
     /// Look up languages by filename.
@@ -205,21 +229,42 @@ impl Language {
     ///
     /// * `Vec<&Language>` - The languages matching the filename
     pub fn find_by_filename(filename: &str) -> Vec<&'static Language> {
-        Self::init();
-        
+        let data = languages::current();
         let basename = std::path::Path::new(filename)
             .file_name()
             .map(|s| s.to_string_lossy().to_string())
             .unwrap_or_default();
         
-        unsafe {
-            FILENAME_INDEX
-                .as_ref()
-                .unwrap()
-                .get(&basename)
-                .map(|idxs| idxs.iter().map(|&idx| &LANGUAGES.as_ref().unwrap()[idx]).collect())
-                .unwrap_or_default()
-        }
+        data.filename_index
+            .get(&basename)
+            .map(|idxs| idxs.iter().map(|&idx| &data.languages[idx]).collect())
+            .unwrap_or_default()
+    }
+    
+    /// Look up languages whose known filenames match a glob pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The glob pattern, e.g. `Docker*`
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&Language>` - The matching languages ordered by ID, empty if the pattern is invalid
+    pub fn find_by_filename_glob(pattern: &str) -> Vec<&'static Language> {
+        let pattern = match glob::Pattern::new(pattern) {
+            Ok(pattern) => pattern,
+            Err(_) => return Vec::new(),
+        };
+        
+        let data = languages::current();
+        let mut indices: Vec<usize> = data.filename_index.iter()
+            .filter(|(filename, _)| pattern.matches(filename))
+            .flat_map(|(_, idxs)| idxs.iter().copied())
+            .collect();
+        indices.sort();
+        indices.dedup();
+        
+        indices.into_iter().map(|idx| &data.languages[idx]).collect()
     }
     
     /// Look up languages by file extension.
@@ -232,8 +277,7 @@ impl Language {
     ///
     /// * `Vec<&Language>` - The languages matching the extension
     pub fn find_by_extension(filename: &str) -> Vec<&'static Language> {
-        Self::init();
-        
+        let data = languages::current();
         let lowercase_filename = filename.to_lowercase();
         let path = std::path::Path::new(&lowercase_filename);
         
@@ -248,12 +292,10 @@ impl Language {
         if let Some(ext) = path.extension() {
             let ext_str = format!(".{}", ext.to_string_lossy().to_lowercase());
             
-            unsafe {
-                if let Some(idxs) = EXTENSION_INDEX.as_ref().unwrap().get(&ext_str) {
-                    if !idxs.is_empty() {
-                        // Only return the first language that matches this extension
-                        return vec![&LANGUAGES.as_ref().unwrap()[idxs[0]]];
-                    }
+            if let Some(idxs) = data.extension_index.get(&ext_str) {
+                if !idxs.is_empty() {
+                    // Only return the first language that matches this extension
+                    return vec![&data.languages[idxs[0]]];
                 }
             }
         }
@@ -261,6 +303,46 @@ impl Language {
         Vec::new()
     }
     
+    /// Look up languages by every extension of a filename.
+    ///
+    /// Unlike `find_by_extension`, this tries all extension suffixes from
+    /// longest to shortest (`.tar.gz`, then `.gz`) and returns every
+    /// matching language for each of them, without duplicates.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - The filename to extract extensions from
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&Language>` - The languages matching any of the extensions
+    pub fn find_by_extension_all(filename: &str) -> Vec<&'static Language> {
+        let data = languages::current();
+        let basename = std::path::Path::new(filename)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let parts: Vec<&str> = basename.split('.').collect();
+        
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        
+        for i in 1..parts.len() {
+            let ext = format!(".{}", parts[i..].join("."));
+            
+            if let Some(idxs) = data.extension_index.get(&ext) {
+                for &idx in idxs {
+                    let language = &data.languages[idx];
+                    if seen.insert(language.name.as_str()) {
+                        result.push(language);
+                    }
+                }
+            }
+        }
+        
+        result
+    }
+    
     /// Look up languages by interpreter.
     ///
     /// # Arguments
@@ -271,16 +353,49 @@ impl Language {
     ///
     /// * `Vec<&Language>` - The languages matching the interpreter
     pub fn find_by_interpreter(interpreter: &str) -> Vec<&'static Language> {
-        Self::init();
-        
-        unsafe {
-            INTERPRETER_INDEX
-                .as_ref()
-                .unwrap()
-                .get(interpreter)
-                .map(|idxs| idxs.iter().map(|&idx| &LANGUAGES.as_ref().unwrap()[idx]).collect())
-                .unwrap_or_default()
+        let data = languages::current();
+        
+        data.interpreter_index
+            .get(interpreter)
+            .map(|idxs| idxs.iter().map(|&idx| &data.languages[idx]).collect())
+            .unwrap_or_default()
+    }
+    
+    /// Get the file extensions shared by more than one language.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, Vec<&Language>)>` - Lowercase extensions and their languages, sorted by extension
+    pub fn ambiguous_extensions() -> Vec<(String, Vec<&'static Language>)> {
+        let mut by_extension: std::collections::BTreeMap<String, Vec<&'static Language>> = std::collections::BTreeMap::new();
+        
+        for language in Self::all() {
+            for ext in &language.extensions {
+                let languages = by_extension.entry(ext.to_lowercase()).or_default();
+                if !languages.iter().any(|l| l.name == language.name) {
+                    languages.push(language);
+                }
+            }
         }
+        
+        by_extension.into_iter()
+            .filter(|(_, languages)| languages.len() > 1)
+            .collect()
+    }
+    
+    /// Look up languages by a shebang line.
+    ///
+    /// # Arguments
+    ///
+    /// * `first_line` - The shebang line, e.g. `#!/usr/bin/env python3`
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&Language>` - The languages matching the shebang's interpreter
+    pub fn find_by_shebangs(first_line: &str) -> Vec<&'static Language> {
+        crate::strategy::shebang::Shebang::interpreter(first_line.as_bytes())
+            .map(|interpreter| Self::find_by_interpreter(&interpreter))
+            .unwrap_or_default()
     }
     
     /// Get a language by its ID.
@@ -293,15 +408,11 @@ impl Language {
     ///
     /// * `Option<&Language>` - The language if found, None otherwise
     pub fn find_by_id(id: usize) -> Option<&'static Language> {
-        Self::init();
-        
-        unsafe {
-            LANGUAGE_ID_INDEX
-                .as_ref()
-                .unwrap()
-                .get(&id)
-                .map(|&idx| &LANGUAGES.as_ref().unwrap()[idx])
-        }
+        let data = languages::current();
+        
+        data.language_id_index
+            .get(&id)
+            .map(|&idx| &data.languages[idx])
     }
     
     /// Language lookup by name or alias.
@@ -332,14 +443,12 @@ impl Language {
     ///
     /// * `Vec<&Language>` - The popular languages
     pub fn popular() -> Vec<&'static Language> {
-        Self::init();
-        
         let mut popular = Self::all()
             .iter()
             .filter(|lang| lang.popular)
             .collect::<Vec<_>>();
         
-        popular.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        popular.sort_by(|a, b| Self::compare_by_id(a, b));
         popular
     }
     
@@ -349,8 +458,6 @@ impl Language {
     ///
     /// * `Vec<&Language>` - The unpopular languages
     pub fn unpopular() -> Vec<&'static Language> {
-        Self::init();
-        
         let mut unpopular = Self::all()
             .iter()
             .filter(|lang| !lang.popular)
@@ -366,17 +473,111 @@ impl Language {
     ///
     /// * `Vec<&Language>` - The languages with colors
     pub fn colors() -> Vec<&'static Language> {
-        Self::init();
-        
         let mut colors = Self::all()
             .iter()
             .filter(|lang| lang.color.is_some())
             .collect::<Vec<_>>();
         
-        colors.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        colors.sort_by(|a, b| Self::compare_by_id(a, b));
         colors
     }
     
+    /// Get a map of MIME types to languages, e.g. for `Content-Type` headers.
+    ///
+    /// Every `codemirror_mime_type` is included. Languages without one get a
+    /// MIME type derived from their type, which never replaces an explicit one.
+    /// When several languages share a MIME type, popular languages win over
+    /// unpopular ones, then the lowest language ID wins.
+    ///
+    /// # Returns
+    ///
+    /// * `&HashMap<String, &Language>` - MIME types mapped to their language
+    pub fn all_mime_types() -> &'static HashMap<String, &'static Language> {
+        let data = languages::current();
+        
+        data.mime_types.get_or_init(|| {
+            let mut mime_types = HashMap::new();
+            
+            // Popular languages first, then in language ID order
+            let mut languages: Vec<&'static Language> = data.languages.iter().collect();
+            languages.sort_by_key(|lang| lang.popularity_rank.is_none());
+            
+            for &language in &languages {
+                if let Some(mime_type) = &language.codemirror_mime_type {
+                    mime_types.entry(mime_type.clone()).or_insert(language);
+                }
+            }
+            
+            for language in languages.into_iter().filter(|lang| lang.codemirror_mime_type.is_none()) {
+                mime_types.entry(language.default_mime_type()).or_insert(language);
+            }
+            
+            mime_types
+        })
+    }
+    
+    /// Derive a MIME type for a language without a `codemirror_mime_type`.
+    fn default_mime_type(&self) -> String {
+        let name = self.name.to_lowercase();
+        
+        match self.language_type {
+            LanguageType::Data if name.contains("json") => "application/json".to_string(),
+            LanguageType::Data if name.contains("yaml") => "application/yaml".to_string(),
+            LanguageType::Data | LanguageType::Markup if name.contains("xml") => "application/xml".to_string(),
+            LanguageType::Markup => "text/html".to_string(),
+            LanguageType::Prose => "text/plain".to_string(),
+            _ => format!("text/x-{}", self.default_alias()),
+        }
+    }
+    
+    /// Parse a single language entry in the format of `languages.yml`.
+    ///
+    /// The language is built on its own and is not added to the
+    /// languages returned by `Language::all()` or the `find_by_*` lookups.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The language name
+    /// * `yaml` - The attributes of the entry (everything after `name:`)
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Language>` - The language, or an error if the attributes aren't a YAML mapping
+    pub fn from_yaml(name: &str, yaml: &str) -> Result<Language> {
+        let attributes: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+        if !attributes.is_mapping() {
+            return Err(Error::Other(format!("Attributes of language {} must be a YAML mapping", name)));
+        }
+        
+        let popularity_rank = languages::get_popular_languages()?
+            .iter()
+            .position(|popular_name| popular_name == name)
+            .map(|position| position + 1);
+        
+        Ok(languages::parse_language(name.to_string(), attributes, popularity_rank))
+    }
+    
+    /// Get the language's definition in the format of `languages.yml`.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - A YAML document mapping the language name to its attributes
+    pub fn yaml_definition(&self) -> String {
+        let mut attributes = match serde_yaml::to_value(self) {
+            Ok(serde_yaml::Value::Mapping(attributes)) => attributes,
+            _ => serde_yaml::Mapping::new(),
+        };
+        
+        // Fields that are derived at load time rather than read from languages.yml
+        for key in ["name", "popular", "popularity_rank"] {
+            attributes.remove(key);
+        }
+        
+        let mut definition = serde_yaml::Mapping::new();
+        definition.insert(self.name.clone().into(), attributes.into());
+        serde_yaml::to_string(&definition).unwrap_or_default()
+    }
+    
     /// Get the default alias for a language.
     ///
     /// # Returns
@@ -392,8 +593,6 @@ impl Language {
     ///
     /// * `Option<&Language>` - The group language if defined
     pub fn group(&self) -> Option<&'static Language> {
-        Self::init();
-        
         let group_name = match &self.group_name {
             Some(name) => name,
             None => &self.name,
@@ -402,6 +601,60 @@ impl Language {
         Self::find_by_name(group_name)
     }
     
+    /// Get the language's group, falling back to the language itself.
+    ///
+    /// # Returns
+    ///
+    /// * `&Language` - The group language, or `self` if the group isn't a known language
+    pub fn resolve_group(&self) -> &Language {
+        self.group().unwrap_or(self)
+    }
+    
+    /// Get all languages in the group this language heads.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&Language>` - This language and every language grouped under it
+    pub fn group_members(&self) -> Vec<&'static Language> {
+        Self::all()
+            .iter()
+            .filter(|lang| lang.name == self.name || lang.group_name.as_deref() == Some(self.name.as_str()))
+            .collect()
+    }
+    
+    /// Get the language's color as red, green and blue components.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(u8, u8, u8)>` - The color, or None if the language has no valid `#rrggbb` color
+    pub fn color_as_rgb(&self) -> Option<(u8, u8, u8)> {
+        let hex = self.color.as_deref()?.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        Some(((value >> 16) as u8, (value >> 8) as u8, value as u8))
+    }
+    
+    /// Get a summary of which optional fields are populated.
+    ///
+    /// # Returns
+    ///
+    /// * `LanguageFeatures` - The populated fields
+    pub fn features(&self) -> LanguageFeatures {
+        LanguageFeatures {
+            has_color: self.color.is_some(),
+            has_tm_scope: self.tm_scope.is_some(),
+            has_ace_mode: self.ace_mode.is_some(),
+            has_codemirror_mode: self.codemirror_mode.is_some(),
+            has_extensions: !self.extensions.is_empty(),
+            has_interpreters: !self.interpreters.is_empty(),
+            has_filenames: !self.filenames.is_empty(),
+            is_popular: self.popular,
+        }
+    }
+    
     /// Check if the language is popular.
     ///
     /// # Returns
@@ -411,6 +664,31 @@ impl Language {
         self.popular
     }
     
+    /// Get the language's position in popular.yml.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The rank (1 is first), or None if the language is not popular
+    pub fn popularity_rank(&self) -> Option<usize> {
+        self.popularity_rank
+    }
+    
+    /// Compare two languages by language ID, for deterministic sorting.
+    ///
+    /// Languages with the same ID are ordered by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first language
+    /// * `b` - The second language
+    ///
+    /// # Returns
+    ///
+    /// * `Ordering` - The ordering of `a` relative to `b`
+    pub fn compare_by_id(a: &Language, b: &Language) -> Ordering {
+        a.language_id.cmp(&b.language_id).then_with(|| a.name.cmp(&b.name))
+    }
+    
     /// Check if the language is not popular.
     ///
     /// # Returns
@@ -429,6 +707,61 @@ impl PartialEq for Language {
 
 impl Eq for Language {}
 
+impl PartialOrd for Language {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Language {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Language::compare_by_id(self, other)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        Language::lookup(s).cloned().ok_or_else(|| Error::UnknownLanguage(s.to_string()))
+    }
+}
+
+impl TryFrom<&str> for Language {
+    type Error = Error;
+    
+    fn try_from(s: &str) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = Error;
+    
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<&'static Language> for Language {
+    fn from(language: &'static Language) -> Self {
+        language.clone()
+    }
+}
+
+// Only the name: the full definition makes assertion failures unreadable
+impl fmt::Debug for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Language").field(&self.name).finish()
+    }
+}
+
 impl Hash for Language {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.name.hash(state);
@@ -439,6 +772,62 @@ impl Hash for Language {
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_display_and_debug() {
+        let ruby = Language::find_by_name("Ruby").unwrap();
+        assert_eq!(ruby.to_string(), "Ruby");
+        assert_eq!(format!("{:?}", ruby), "Language(\"Ruby\")");
+    }
+    
+    #[test]
+    fn test_parse() {
+        let rust: Language = "Rust".parse().unwrap();
+        assert_eq!(rust.name, "Rust");
+        
+        let rust = Language::try_from("rust").unwrap();
+        assert_eq!(rust.name, "Rust");
+        
+        let ruby = Language::from(Language::find_by_name("Ruby").unwrap());
+        assert_eq!(ruby.name, "Ruby");
+        
+        assert!(matches!(
+            "NotALanguage".parse::<Language>(),
+            Err(Error::UnknownLanguage(name)) if name == "NotALanguage"
+        ));
+    }
+    
+    #[test]
+    fn test_deserialize_from_name() {
+        #[derive(Deserialize)]
+        struct Config {
+            language: Language,
+        }
+        
+        let config: Config = serde_json::from_str(r#"{"language": "Python"}"#).unwrap();
+        assert_eq!(config.language.name, "Python");
+        
+        let config: Config = serde_yaml::from_str("language: rb").unwrap();
+        assert_eq!(config.language.name, "Ruby");
+        
+        assert!(serde_json::from_str::<Config>(r#"{"language": "NotALanguage"}"#).is_err());
+    }
+    
+    #[test]
+    fn test_compare_by_id() {
+        let c = Language::find_by_name("C").unwrap();
+        let cpp = Language::find_by_name("C++").unwrap();
+        assert_eq!(Language::compare_by_id(c, cpp), c.language_id.cmp(&cpp.language_id));
+        assert_eq!(c.cmp(c), Ordering::Equal);
+        
+        let mut languages = [cpp.clone(), c.clone()];
+        languages.sort();
+        assert!(languages[0].language_id <= languages[1].language_id);
+        
+        for list in [Language::all().iter().collect::<Vec<_>>(), Language::popular(), Language::colors()] {
+            assert!(list.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+    
     #[test]
     fn test_find_by_name() {
         let rust = Language::find_by_name("Rust").unwrap();
@@ -450,6 +839,40 @@ mod tests {
         assert_eq!(rust.name, "Rust");
     }
     
+    #[test]
+    fn test_all_as_json() {
+        let json = Language::all_as_json();
+        let languages = json.as_array().unwrap();
+        assert_eq!(languages.len(), Language::all().len());
+        
+        let rust = languages.iter().find(|lang| lang["name"] == "Rust").unwrap();
+        assert_eq!(rust["type"], "programming");
+        assert!(rust["extensions"].as_array().unwrap().contains(&serde_json::json!(".rs")));
+        assert!(rust["group"].is_null());
+        assert!(rust.as_object().unwrap().contains_key("fs_name"));
+        
+        let tsx = languages.iter().find(|lang| lang["name"] == "TSX").unwrap();
+        assert_eq!(tsx["group"], "TypeScript");
+    }
+    
+    #[test]
+    fn test_comma_separated_lookup() {
+        assert_eq!(Language::find_by_name("Ruby, version 3").unwrap().name, "Ruby");
+        assert_eq!(Language::find_by_name("version 3, Ruby").unwrap().name, "Ruby");
+        assert_eq!(Language::find_by_alias("js, javascript").unwrap().name, "JavaScript");
+        assert!(Language::find_by_alias("nope, still nope").is_none());
+    }
+    
+    #[test]
+    fn test_find_by_filename_glob() {
+        let languages = Language::find_by_filename_glob("Docker*");
+        assert!(languages.iter().any(|lang| lang.name == "Dockerfile"));
+        assert!(languages.iter().all(|lang| lang.filenames.iter().any(|name| name.starts_with("Docker"))));
+        
+        assert!(Language::find_by_filename_glob("NoSuchFile*").is_empty());
+        assert!(Language::find_by_filename_glob("[").is_empty());
+    }
+    
     #[test]
     fn test_find_by_extension() {
         let rust_langs = Language::find_by_extension("hello.rs");
@@ -461,6 +884,21 @@ mod tests {
         assert_eq!(js_langs[0].name, "JavaScript");
     }
     
+    #[test]
+    fn test_find_by_extension_all() {
+        let header_langs = Language::find_by_extension_all("include/header.h");
+        let names: Vec<_> = header_langs.iter().map(|l| l.name.as_str()).collect();
+        assert!(names.contains(&"C"));
+        assert!(names.contains(&"C++"));
+        assert!(names.contains(&"Objective-C"));
+        
+        // Compound extensions are tried before the last extension
+        let erb_langs = Language::find_by_extension_all("view.erb.deface");
+        assert_eq!(erb_langs[0].name, "HTML+ERB");
+        
+        assert!(Language::find_by_extension_all("README").is_empty());
+    }
+    
     #[test]
     fn test_find_by_filename() {
         let docker_langs = Language::find_by_filename("Dockerfile");
@@ -468,6 +906,83 @@ mod tests {
         assert_eq!(docker_langs[0].name, "Dockerfile");
     }
     
+    #[test]
+    fn test_ambiguous_extensions() {
+        let ambiguous = Language::ambiguous_extensions();
+        
+        let (_, languages) = ambiguous.iter().find(|(ext, _)| ext == ".h").unwrap();
+        assert!(languages.iter().any(|l| l.name == "C"));
+        assert!(languages.iter().any(|l| l.name == "C++"));
+        
+        assert!(ambiguous.iter().all(|(_, languages)| languages.len() > 1));
+        assert!(!ambiguous.iter().any(|(ext, _)| ext == ".go" || ext == ".py"));
+    }
+    
+    #[test]
+    fn test_find_by_shebangs() {
+        let languages = Language::find_by_shebangs("#!/usr/bin/env python3");
+        assert!(languages.iter().any(|l| l.name == "Python"));
+        
+        let languages = Language::find_by_shebangs("#!/bin/bash -e");
+        assert!(languages.iter().any(|l| l.name == "Shell"));
+        
+        assert!(Language::find_by_shebangs("not a shebang").is_empty());
+    }
+    
+    #[test]
+    fn test_all_mime_types() {
+        let mime_types = Language::all_mime_types();
+        
+        // Explicit codemirror MIME types
+        assert_eq!(mime_types["text/x-rustsrc"].name, "Rust");
+        assert_eq!(mime_types["text/html"].name, "HTML");
+        
+        // Shared MIME types go to the popular language
+        assert_eq!(mime_types["text/x-python"].name, "Python");
+        
+        // Every language is reachable through some MIME type or shares one
+        for language in Language::all() {
+            let mime_type = language.codemirror_mime_type.clone()
+                .unwrap_or_else(|| language.default_mime_type());
+            assert!(mime_types.contains_key(&mime_type), "{} missing", mime_type);
+        }
+    }
+    
+    #[test]
+    fn test_yaml_definition() {
+        let rust = Language::find_by_name("Rust").unwrap();
+        let definition: serde_yaml::Value = serde_yaml::from_str(&rust.yaml_definition()).unwrap();
+        
+        let languages: serde_yaml::Value = serde_yaml::from_str(include_str!("../data/languages.yml")).unwrap();
+        assert_eq!(definition["Rust"], languages["Rust"]);
+    }
+    
+    #[test]
+    fn test_from_yaml() -> Result<()> {
+        let yaml = "type: programming\ncolor: \"#123456\"\nextensions:\n- \".widget\"\nlanguage_id: 999999\n";
+        let language = Language::from_yaml("Widget", yaml)?;
+        assert_eq!(language.name, "Widget");
+        assert_eq!(language.language_type, LanguageType::Programming);
+        assert_eq!(language.color.as_deref(), Some("#123456"));
+        assert_eq!(language.extensions, vec![".widget".to_string()]);
+        assert_eq!(language.aliases, vec!["widget".to_string()]);
+        assert_eq!(language.language_id, 999999);
+        
+        // The language stays out of the shared data
+        assert!(Language::find_by_name("Widget").is_none());
+        
+        // Round trip through yaml_definition
+        let rust = Language::find_by_name("Rust").unwrap();
+        let definition: serde_yaml::Value = serde_yaml::from_str(&rust.yaml_definition())?;
+        let parsed = Language::from_yaml("Rust", &serde_yaml::to_string(&definition["Rust"])?)?;
+        assert_eq!(parsed.yaml_definition(), rust.yaml_definition());
+        assert_eq!(parsed.popularity_rank, rust.popularity_rank);
+        
+        assert!(Language::from_yaml("Widget", "- not\n- a mapping\n").is_err());
+        
+        Ok(())
+    }
+    
     #[test]
     fn test_popular_languages() {
         let popular = Language::popular();
@@ -475,4 +990,68 @@ mod tests {
         assert!(popular.iter().any(|l| l.name == "JavaScript"));
         assert!(popular.iter().any(|l| l.name == "Python"));
     }
+    
+    #[test]
+    fn test_resolve_group() {
+        let opencl = Language::find_by_name("OpenCL").unwrap();
+        assert_eq!(opencl.resolve_group().name, "C");
+        
+        let c = Language::find_by_name("C").unwrap();
+        assert_eq!(c.resolve_group().name, "C");
+        
+        // Languages outside the loaded data are their own group
+        let widget = Language::from_yaml("Widget", "type: programming\ngroup: Gadget\n").unwrap();
+        assert_eq!(widget.resolve_group().name, "Widget");
+    }
+    
+    #[test]
+    fn test_group_members() {
+        let c = Language::find_by_name("C").unwrap();
+        let members: Vec<&str> = c.group_members().iter().map(|l| l.name.as_str()).collect();
+        assert!(members.contains(&"C"));
+        assert!(members.contains(&"OpenCL"));
+        assert!(members.iter().all(|name| Language::find_by_name(name).unwrap().group().unwrap().name == "C"));
+        
+        // A grouped language heads no group of its own
+        let opencl = Language::find_by_name("OpenCL").unwrap();
+        assert_eq!(opencl.group_members().len(), 1);
+    }
+    
+    #[test]
+    fn test_color_as_rgb() {
+        let rust = Language::find_by_name("Rust").unwrap();
+        assert_eq!(rust.color.as_deref(), Some("#dea584"));
+        assert_eq!(rust.color_as_rgb(), Some((0xde, 0xa5, 0x84)));
+        
+        let mut language = rust.clone();
+        language.color = None;
+        assert_eq!(language.color_as_rgb(), None);
+        
+        language.color = Some("#+12345".to_string());
+        assert_eq!(language.color_as_rgb(), None);
+    }
+    
+    #[test]
+    fn test_features() {
+        let features = Language::find_by_name("Python").unwrap().features();
+        assert!(features.has_color);
+        assert!(features.has_extensions);
+        assert!(features.has_interpreters);
+        assert!(features.is_popular);
+        
+        let features = Language::find_by_name("Dockerfile").unwrap().features();
+        assert!(features.has_filenames);
+        assert!(!features.has_interpreters);
+    }
+    
+    #[test]
+    fn test_popularity_rank() {
+        // C is the first entry in popular.yml
+        assert_eq!(Language::find_by_name("C").unwrap().popularity_rank(), Some(1));
+        assert!(Language::find_by_name("Python").unwrap().popularity_rank().is_some());
+        
+        for language in Language::all() {
+            assert_eq!(language.popularity_rank().is_some(), language.is_popular());
+        }
+    }
 }
\ No newline at end of file
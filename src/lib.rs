@@ -13,7 +13,11 @@ pub mod strategy;
 pub mod vendor;
 pub mod data;
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+
+use dashmap::DashMap;
+use sha2::{Digest, Sha256};
 use language::Language;
 use strategy::{Strategy, StrategyType};
 
@@ -55,22 +59,167 @@ pub enum Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-// Strategies used to detect languages, in order of priority
-lazy_static::lazy_static! {
-    static ref STRATEGIES: Vec<StrategyType> = vec![
+/// Build the detection strategies, in order of priority
+fn build_strategies() -> Result<Vec<StrategyType>> {
+    Ok(vec![
         StrategyType::Modeline(strategy::modeline::Modeline),
+        StrategyType::ContentType(strategy::content_type::ContentType::default()),
         StrategyType::Filename(strategy::filename::Filename),
         StrategyType::Shebang(strategy::shebang::Shebang),
+        StrategyType::Script(strategy::script::Script),
         StrategyType::Extension(strategy::extension::Extension),
         StrategyType::Xml(strategy::xml::Xml),
         StrategyType::Manpage(strategy::manpage::Manpage),
-        StrategyType::Heuristics(heuristics::Heuristics),
-        StrategyType::Classifier(classifier::Classifier),
-    ];
+        StrategyType::Heuristics(heuristics::Heuristics::new_from_bundled()?),
+        StrategyType::Classifier(classifier::Classifier::default()),
+    ])
+}
+
+/// Get the detection strategies, building them on first use
+///
+/// The strategies belong to the language data in use, so `reload` replaces them.
+///
+/// # Returns
+///
+/// * `Result<&[StrategyType]>` - The strategies in order of priority, or the error that prevented building them
+pub(crate) fn strategies() -> Result<&'static [StrategyType]> {
+    match data::languages::current().strategies.get_or_init(build_strategies) {
+        Ok(strategies) => Ok(strategies),
+        Err(err) => Err(Error::Other(format!("Failed to build detection strategies: {}", err))),
+    }
+}
+
+/// Get the fast detection strategies, building them on first use
+///
+/// # Returns
+///
+/// * `Result<&[StrategyType]>` - The standard strategies without Xml and Classifier
+pub(crate) fn fast_strategies() -> Result<&'static [StrategyType]> {
+    let strategies = strategies()?;
+    
+    Ok(data::languages::current().fast_strategies.get_or_init(|| {
+        strategies.iter()
+            .filter(|strategy| !matches!(strategy, StrategyType::Xml(_) | StrategyType::Classifier(_)))
+            .cloned()
+            .collect()
+    }))
+}
+
+/// Options controlling language detection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetectionConfig {
+    /// Whether to detect the language of empty files
+    pub allow_empty: bool,
+    
+    /// Whether to read and populate the process-wide detection cache
+    pub global_cache: bool,
+}
+
+impl DetectionConfig {
+    /// Opt in to (or out of) the process-wide detection cache
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether detections should go through `global_detection_cache()`
+    ///
+    /// # Returns
+    ///
+    /// * `DetectionConfig` - The updated configuration
+    pub fn use_global_cache(mut self, enabled: bool) -> Self {
+        self.global_cache = enabled;
+        self
+    }
+}
+
+/// Cache of detection results keyed by blob name and content
+///
+/// Content is identified by its length and SHA-256 digest, so distinct
+/// files can't share a result.
+#[derive(Debug, Default)]
+pub struct DetectionCache {
+    entries: DashMap<(String, bool, usize, [u8; 32]), Option<Language>>,
+}
+
+impl DetectionCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Detect the language of a blob, reusing a cached result when the
+    /// blob's name and content have been seen before
+    ///
+    /// Blobs with a language override or a `Content-Type` are detected
+    /// without the cache, since the key doesn't capture either.
+    ///
+    /// # Arguments
+    ///
+    /// * `blob` - The blob to analyze
+    /// * `allow_empty` - Whether to allow empty files
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Language>` - The detected language or None if undetermined
+    pub fn detect<B: BlobHelper + ?Sized>(&self, blob: &B, allow_empty: bool) -> Option<Language> {
+        if blob.language_override().is_some() || blob.content_type().is_some() {
+            return detect(blob, allow_empty);
+        }
+        
+        let data = blob.data();
+        let key = (blob.name().to_string(), allow_empty, data.len(), Sha256::digest(data).into());
+        
+        if let Some(cached) = self.entries.get(&key) {
+            return cached.clone();
+        }
+        
+        let language = detect(blob, allow_empty);
+        self.entries.insert(key, language.clone());
+        language
+    }
+    
+    /// Number of cached results
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Whether the cache holds no results
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    /// Drop all cached results
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+}
+
+static GLOBAL_DETECTION_CACHE: OnceLock<DetectionCache> = OnceLock::new();
+
+/// Get the process-wide detection cache
+///
+/// The cache is only used by `detect_with_config` when
+/// `DetectionConfig::use_global_cache(true)` is set.
+pub fn global_detection_cache() -> &'static DetectionCache {
+    GLOBAL_DETECTION_CACHE.get_or_init(DetectionCache::new)
+}
+
+/// Drop all results from the process-wide detection cache
+///
+/// Long-running processes should call this once files they have
+/// analyzed may have changed.
+pub fn clear_global_detection_cache() {
+    if let Some(cache) = GLOBAL_DETECTION_CACHE.get() {
+        cache.clear();
+    }
 }
 
 /// Detects the language of a blob.
 ///
+/// A language forced with `BlobHelper::language_override` is returned as is.
+/// Files whose extension belongs to a single language and has no
+/// disambiguation rule are detected from the extension without running
+/// the strategies.
+///
 /// # Arguments
 ///
 /// * `blob` - A blob object implementing the BlobHelper trait
@@ -80,18 +229,133 @@ lazy_static::lazy_static! {
 ///
 /// * `Option<Language>` - The detected language or None if undetermined
 pub fn detect<B: BlobHelper + ?Sized>(blob: &B, allow_empty: bool) -> Option<Language> {
+    detect_with_strategies(blob, allow_empty, strategies())
+}
+
+/// Detects the language of a blob without the slower content-based strategies.
+///
+/// Runs the standard pipeline without the Xml and Classifier strategies,
+/// which suits interactive use such as editor hovers. Files that only the
+/// classifier could tell apart stay undetermined.
+///
+/// # Arguments
+///
+/// * `blob` - A blob object implementing the BlobHelper trait
+/// * `allow_empty` - Whether to allow empty files
+///
+/// # Returns
+///
+/// * `Option<Language>` - The detected language or None if undetermined
+pub fn detect_fast<B: BlobHelper + ?Sized>(blob: &B, allow_empty: bool) -> Option<Language> {
+    detect_with_strategies(blob, allow_empty, fast_strategies())
+}
+
+/// Get the extensions that belong to exactly one language and have no disambiguation rule
+///
+/// The set is stored with the language data, so it is rebuilt after
+/// `data::languages::reload`.
+///
+/// # Returns
+///
+/// * `&HashSet<String>` - Lowercase extensions, including the leading dot
+fn unambiguous_extensions() -> &'static HashSet<String> {
+    let data = data::languages::current();
+    data.unambiguous_extensions.get_or_init(|| {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for language in &data.languages {
+            let extensions: HashSet<String> = language.extensions.iter()
+                .map(|ext| ext.to_lowercase())
+                .collect();
+            for ext in extensions {
+                *counts.entry(ext).or_insert(0) += 1;
+            }
+        }
+        
+        let disambiguated: HashSet<String> = strategies().unwrap_or_default().iter()
+            .filter_map(|strategy| match strategy {
+                StrategyType::Heuristics(heuristics) => Some(heuristics.coverage()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        
+        counts.into_iter()
+            .filter(|(ext, count)| *count == 1 && !disambiguated.contains(ext))
+            .map(|(ext, _)| ext)
+            .collect()
+    })
+}
+
+/// Detect the language of a blob from an unambiguous extension alone
+///
+/// Skipped for blobs whose filename, `Content-Type`, modeline or shebang
+/// could decide otherwise.
+///
+/// # Arguments
+///
+/// * `blob` - The blob to check
+///
+/// # Returns
+///
+/// * `Option<Language>` - The language, or None if the strategies need to run
+fn detect_unambiguous_extension<B: BlobHelper + ?Sized>(blob: &B) -> Option<Language> {
+    let extension = blob.extension()?.to_lowercase();
+    if !unambiguous_extensions().contains(&extension)
+        || blob.is_symlink()
+        || blob.content_type().is_some()
+        || !Language::find_by_filename(blob.name()).is_empty()
+    {
+        return None;
+    }
+    
+    // Explicit signals in the content take precedence over the extension
+    if !strategy::modeline::Modeline.call(blob, &[]).is_empty()
+        || !strategy::shebang::Shebang.call(blob, &[]).is_empty()
+    {
+        return None;
+    }
+    
+    // A compound extension (`.html.erb`) may belong to another language
+    match Language::find_by_extension_all(blob.name()).as_slice() {
+        [language] => Some((*language).clone()),
+        _ => None,
+    }
+}
+
+/// Run detection strategies in order until one settles on a language
+fn detect_with_strategies<B: BlobHelper + ?Sized>(
+    blob: &B,
+    allow_empty: bool,
+    strategies: Result<&[StrategyType]>
+) -> Option<Language> {
+    if let Some(language) = blob.language_override() {
+        return Some(language.clone());
+    }
+    
     // Bail early if the blob is binary or empty
     if blob.likely_binary() || blob.is_binary() || (!allow_empty && blob.is_empty()) {
         return None;
     }
-
+    
+    if let Some(language) = detect_unambiguous_extension(blob) {
+        return Some(language);
+    }
+    
+    let strategies = match strategies {
+        Ok(strategies) => strategies,
+        Err(err) => {
+            log::error!("{}", err);
+            return None;
+        }
+    };
+    
     let mut candidates = Vec::new();
     
-    // Try each strategy until one returns a single candidate
-    for strategy in STRATEGIES.iter() {
+    // Try each strategy until a definitive one returns a single candidate
+    for strategy in strategies {
         let result = strategy.call(blob, &candidates);
         
-        if result.len() == 1 {
+        if result.len() == 1 && strategy.is_definitive() {
             return result.into_iter().next();
         } else if !result.is_empty() {
             candidates = result;
@@ -106,6 +370,75 @@ pub fn detect<B: BlobHelper + ?Sized>(blob: &B, allow_empty: bool) -> Option<Lan
     }
 }
 
+/// Detects the language of a blob along with the strategy that decided it.
+///
+/// # Arguments
+///
+/// * `blob` - A blob object implementing the BlobHelper trait
+/// * `allow_empty` - Whether to allow empty files
+///
+/// # Returns
+///
+/// * `Option<(Language, &'static str)>` - The detected language and the name of the deciding strategy
+pub fn detect_first_match<B: BlobHelper + ?Sized>(blob: &B, allow_empty: bool) -> Option<(Language, &'static str)> {
+    if blob.likely_binary() || blob.is_binary() || (!allow_empty && blob.is_empty()) {
+        return None;
+    }
+    
+    if let Some(language) = detect_unambiguous_extension(blob) {
+        return Some((language, "Extension"));
+    }
+    
+    let strategies = match strategies() {
+        Ok(strategies) => strategies,
+        Err(err) => {
+            log::error!("{}", err);
+            return None;
+        }
+    };
+    
+    let mut candidates = Vec::new();
+    let mut narrowed_by = None;
+    
+    for strategy in strategies {
+        let result = strategy.call(blob, &candidates);
+        
+        if result.len() == 1 && strategy.is_definitive() {
+            return result.into_iter().next().map(|language| (language, strategy.name()));
+        } else if !result.is_empty() {
+            // Pass-through strategies keep the credit with whoever narrowed the list
+            if result != candidates {
+                narrowed_by = Some(strategy.name());
+            }
+            candidates = result;
+        }
+    }
+    
+    if candidates.len() == 1 {
+        candidates.into_iter().next().zip(narrowed_by)
+    } else {
+        None
+    }
+}
+
+/// Detects the language of a blob using the given detection options.
+///
+/// # Arguments
+///
+/// * `blob` - A blob object implementing the BlobHelper trait
+/// * `config` - The detection options
+///
+/// # Returns
+///
+/// * `Option<Language>` - The detected language or None if undetermined
+pub fn detect_with_config<B: BlobHelper + ?Sized>(blob: &B, config: &DetectionConfig) -> Option<Language> {
+    if config.global_cache {
+        global_detection_cache().detect(blob, config.allow_empty)
+    } else {
+        detect(blob, config.allow_empty)
+    }
+}
+
 /// Detects the language of a blob (simplified from parallel version).
 ///
 /// # Arguments
@@ -123,22 +456,47 @@ pub fn detect_parallel<B: BlobHelper + Send + Sync + 'static>(blob: Arc<B>, allo
 
 /// Batch detect languages for multiple blobs in parallel
 ///
+/// Blobs are processed in no particular order, but the results are
+/// returned in input order: `results[i]` is the language of `blobs[i]`.
+///
 /// # Arguments
 ///
-/// * `blobs` - Vector of blobs to analyze
+/// * `blobs` - Slice of blobs to analyze
 /// * `allow_empty` - Whether to allow empty files
 ///
 /// # Returns
 ///
-/// * `Vec<Option<Language>>` - Detected languages for each blob
-pub fn detect_batch_parallel<B: BlobHelper + Send + Sync + 'static>(
-    blobs: Vec<Arc<B>>, 
+/// * `Vec<Option<Language>>` - Detected languages for each blob, in input order
+pub fn detect_batch_parallel<B: BlobHelper + Sync>(
+    blobs: &[B],
     allow_empty: bool
 ) -> Vec<Option<Language>> {
     use rayon::prelude::*;
     
     blobs.par_iter()
-        .map(|blob| detect_parallel(blob.clone(), allow_empty))
+        .map(|blob| detect(blob, allow_empty))
+        .collect()
+}
+
+/// Batch detect languages for multiple blobs on the current thread
+///
+/// Blobs are processed strictly in input order, which makes runs
+/// reproducible when debugging.
+///
+/// # Arguments
+///
+/// * `blobs` - Slice of blobs to analyze
+/// * `allow_empty` - Whether to allow empty files
+///
+/// # Returns
+///
+/// * `Vec<Option<Language>>` - Detected languages for each blob, in input order
+pub fn detect_batch_sequential<B: BlobHelper>(
+    blobs: &[B],
+    allow_empty: bool
+) -> Vec<Option<Language>> {
+    blobs.iter()
+        .map(|blob| detect(blob, allow_empty))
         .collect()
 }
 
@@ -158,6 +516,168 @@ mod tests {
         assert_eq!(language.name, "Ruby");
     }
     
+    #[test]
+    fn test_non_definitive_strategies() {
+        assert!(!StrategyType::Xml(strategy::xml::Xml).is_definitive());
+        assert!(!StrategyType::Manpage(strategy::manpage::Manpage).is_definitive());
+        assert!(StrategyType::Extension(strategy::extension::Extension).is_definitive());
+        
+        // A non-definitive result still decides when nothing else narrows it down
+        let content = "<?xml version=\"1.0\"?>\n<root></root>";
+        let blob = FileBlob::from_data(Path::new("data"), content.as_bytes().to_vec());
+        assert_eq!(detect(&blob, false).unwrap().name, "XML");
+    }
+    
+    #[test]
+    fn test_unambiguous_extension() {
+        assert!(unambiguous_extensions().contains(".go"));
+        assert!(!unambiguous_extensions().contains(".h"));
+        assert!(!unambiguous_extensions().contains(".txt"));
+        
+        let blob = FileBlob::from_data(Path::new("main.go"), b"package main\n".to_vec());
+        assert_eq!(detect_unambiguous_extension(&blob).unwrap().name, "Go");
+        assert_eq!(detect(&blob, false).unwrap().name, "Go");
+        
+        let blob = FileBlob::from_data(Path::new("main.go"), b"package main\n".to_vec())
+            .with_content_type("text/x-python");
+        assert!(detect_unambiguous_extension(&blob).is_none());
+        
+        let blob = FileBlob::from_data(Path::new("vector.h"), b"#include <vector>\n".to_vec());
+        assert!(detect_unambiguous_extension(&blob).is_none());
+        
+        // Shebangs and modelines win over the extension
+        let blob = FileBlob::from_data(Path::new("script.rb"), b"#!/usr/bin/env python\nprint(1)\n".to_vec());
+        assert!(detect_unambiguous_extension(&blob).is_none());
+        assert_eq!(detect(&blob, false).unwrap().name, "Python");
+        assert_eq!(detect_first_match(&blob, false).unwrap(), (detect(&blob, false).unwrap(), "Shebang"));
+        
+        let blob = FileBlob::from_data(Path::new("script.rb"), b"# -*- mode: python -*-\nprint(1)\n".to_vec());
+        assert_eq!(detect(&blob, false).unwrap().name, "Python");
+        assert_eq!(detect_first_match(&blob, false).unwrap().1, "Modeline");
+    }
+    
+    #[test]
+    fn test_detect_first_match() {
+        let blob = FileBlob::from_data(Path::new("main.rs"), b"fn main() {}".to_vec());
+        let (language, strategy) = detect_first_match(&blob, false).unwrap();
+        assert_eq!(language.name, "Rust");
+        assert_eq!(strategy, "Extension");
+        
+        let blob = FileBlob::from_data(Path::new("script"), b"#!/usr/bin/env ruby\nputs 1".to_vec());
+        assert_eq!(detect_first_match(&blob, false).unwrap().1, "Shebang");
+        
+        let blob = FileBlob::from_data(Path::new("data"), b"<?xml version=\"1.0\"?>\n<root/>".to_vec());
+        assert_eq!(detect_first_match(&blob, false).unwrap().1, "Xml");
+    }
+    
+    #[test]
+    fn test_detect_fast() {
+        let names = |strategies: &[StrategyType]| strategies.iter().map(StrategyType::name).collect::<Vec<_>>();
+        let fast = names(fast_strategies().unwrap());
+        assert!(!fast.contains(&"Xml"));
+        assert!(!fast.contains(&"Classifier"));
+        assert_eq!(fast.len(), strategies().unwrap().len() - 2);
+        
+        let blob = FileBlob::from_data(Path::new("main.rs"), b"fn main() {}".to_vec());
+        assert_eq!(blob.detect_language_fast().unwrap().name, "Rust");
+        
+        let blob = FileBlob::from_data(Path::new("script"), b"#!/usr/bin/env python\nprint('hi')".to_vec());
+        assert_eq!(blob.detect_language_fast().unwrap().name, "Python");
+        
+        // XML without an extension needs the Xml strategy
+        let blob = FileBlob::from_data(Path::new("data"), b"<?xml version=\"1.0\"?>\n<root/>".to_vec());
+        assert_eq!(blob.language().unwrap().name, "XML");
+        assert!(blob.detect_language_fast().is_none());
+    }
+    
+    #[test]
+    fn test_detect_batch_preserves_order() {
+        let files = [
+            ("main.rs", "Rust"),
+            ("script.py", "Python"),
+            ("app.rb", "Ruby"),
+            ("server.go", "Go"),
+        ];
+        
+        let blobs: Vec<FileBlob> = (0..100)
+            .map(|i| {
+                let (name, _) = files[i % files.len()];
+                FileBlob::from_data(Path::new(&format!("{}/{}", i, name)), b"x".to_vec())
+            })
+            .collect();
+        
+        let parallel = detect_batch_parallel(&blobs, false);
+        let sequential = detect_batch_sequential(&blobs, false);
+        assert_eq!(parallel.len(), 100);
+        assert_eq!(parallel, sequential);
+        
+        for (i, language) in parallel.iter().enumerate() {
+            let (_, expected) = files[i % files.len()];
+            assert_eq!(language.as_ref().unwrap().name, expected, "result {} out of order", i);
+        }
+    }
+    
+    #[test]
+    fn test_detect_with_config() {
+        let blob = FileBlob::from_data(Path::new("empty.rb"), Vec::new());
+        
+        assert!(detect_with_config(&blob, &DetectionConfig::default()).is_none());
+        
+        let config = DetectionConfig { allow_empty: true, ..Default::default() };
+        assert_eq!(blob.detect_with_config(&config).unwrap().name, "Ruby");
+        
+        // Overrides apply with and without the cache
+        let blob = FileBlob::from_data(Path::new("main.rs"), b"fn main() {}".to_vec())
+            .with_language_override("C")
+            .unwrap();
+        assert_eq!(detect(&blob, false).unwrap().name, "C");
+        assert_eq!(blob.detect_with_config(&DetectionConfig::default()).unwrap().name, "C");
+        let cached = DetectionConfig::default().use_global_cache(true);
+        assert_eq!(blob.detect_with_config(&cached).unwrap().name, "C");
+    }
+    
+    #[test]
+    fn test_global_detection_cache() {
+        let blob = FileBlob::from_data(Path::new("cached_global.rs"), b"fn main() {}".to_vec());
+        let key_count = |cache: &DetectionCache| cache.entries.iter()
+            .filter(|entry| entry.key().0 == "cached_global.rs")
+            .count();
+        
+        // Disabled by default
+        assert_eq!(detect_with_config(&blob, &DetectionConfig::default()).unwrap().name, "Rust");
+        assert_eq!(key_count(global_detection_cache()), 0);
+        
+        let config = DetectionConfig::default().use_global_cache(true);
+        assert_eq!(detect_with_config(&blob, &config).unwrap().name, "Rust");
+        assert_eq!(detect_with_config(&blob, &config).unwrap().name, "Rust");
+        assert_eq!(key_count(global_detection_cache()), 1);
+        
+        clear_global_detection_cache();
+        assert_eq!(key_count(global_detection_cache()), 0);
+    }
+    
+    #[test]
+    fn test_detection_cache_key() {
+        let cache = DetectionCache::new();
+        
+        // The same name with different content is detected separately
+        let python = FileBlob::from_data(Path::new("script"), b"#!/usr/bin/env python\nprint('hi')".to_vec());
+        let ruby = FileBlob::from_data(Path::new("script"), b"#!/usr/bin/env ruby\nputs 'hi'".to_vec());
+        assert_eq!(cache.detect(&python, false).unwrap().name, "Python");
+        assert_eq!(cache.detect(&ruby, false).unwrap().name, "Ruby");
+        assert_eq!(cache.len(), 2);
+        
+        // Blobs whose result doesn't depend only on name and content bypass the cache
+        let typed = FileBlob::from_data(Path::new("script"), b"#!/usr/bin/env python\nprint('hi')".to_vec())
+            .with_content_type("text/x-ruby");
+        assert_eq!(cache.detect(&typed, false), detect(&typed, false));
+        let overridden = FileBlob::from_data(Path::new("script"), b"#!/usr/bin/env python\nprint('hi')".to_vec())
+            .with_language_override("Perl")
+            .unwrap();
+        cache.detect(&overridden, false);
+        assert_eq!(cache.len(), 2);
+    }
+    
     
     // Add more tests for different language detection scenarios
 }
\ No newline at end of file
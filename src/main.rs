@@ -2,14 +2,24 @@
 //!
 //! This provides command-line functionality for analyzing files and repositories.
 
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::process;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use git2::Repository as GitRepo;
 
-use linguist::blob::{FileBlob, BlobHelper};  // Added BlobHelper trait import
-use linguist::repository::DirectoryAnalyzer;
+use linguist::blob::{EncodingConfidence, FileBlob, BlobHelper};
+use linguist::heuristics::{self, Heuristics};
+use linguist::language::{Language, LanguageType};
+use linguist::repository::{DirectoryAnalyzer, LanguageChange, LanguageStats, LanguageStatsDiff};
+use linguist::strategy::shebang::Shebang;
+use linguist::Repository;
+
+// Quiet period after a file change before the directory is re-analyzed
+#[cfg(feature = "watch")]
+const WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
 
 #[derive(Parser)]
 #[clap(name = "linguist")]
@@ -21,6 +31,66 @@ struct Cli {
     command: Commands,
 }
 
+/// Ordering of the language breakdown
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortBy {
+    /// Alphabetically by language name
+    Name,
+    /// By size in bytes, largest first
+    Size,
+    /// By size, shown as percentages
+    Percentage,
+}
+
+/// Output format of the `analyze` command
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// JSON object of language names to byte counts
+    Json,
+    /// TOML with `[languages]` and `[summary]` sections
+    Toml,
+}
+
+/// TOML document written by `analyze --format toml`
+#[derive(serde::Serialize)]
+struct TomlReport<'a> {
+    languages: BTreeMap<&'a str, usize>,
+    summary: TomlSummary<'a>,
+}
+
+#[derive(serde::Serialize)]
+struct TomlSummary<'a> {
+    total_bytes: usize,
+    primary_language: Option<&'a str>,
+}
+
+/// Language types that the language breakdown can be restricted to
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TypeFilter {
+    Programming,
+    Markup,
+    Data,
+    Prose,
+}
+
+impl TypeFilter {
+    /// Check whether a language of the given name has this type
+    fn matches(self, language: &str) -> bool {
+        let expected = match self {
+            TypeFilter::Programming => LanguageType::Programming,
+            TypeFilter::Markup => LanguageType::Markup,
+            TypeFilter::Data => LanguageType::Data,
+            TypeFilter::Prose => LanguageType::Prose,
+        };
+        
+        Language::find_by_name(language)
+            .map(|language| language.language_type == expected)
+            .unwrap_or(false)
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Detect the language of a file
@@ -28,6 +98,14 @@ enum Commands {
         /// Path to the file
         #[clap(value_parser)]
         path: PathBuf,
+        
+        /// Show line counts
+        #[clap(long)]
+        loc: bool,
+        
+        /// Show the detected encoding
+        #[clap(long)]
+        encoding: bool,
     },
     
     /// Analyze a directory or repository
@@ -40,14 +118,103 @@ enum Commands {
         #[clap(short, long)]
         breakdown: bool,
         
+        /// Also list the files left out of the statistics with the reason
+        #[clap(long, requires = "breakdown")]
+        show_excluded: bool,
+        
         /// Show percentages instead of byte counts
         #[clap(short, long)]
         percentage: bool,
         
+        /// Output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+        
+        /// Use JSON output format (deprecated, use `--format json`)
+        #[clap(short, long, hide = true, conflicts_with = "format")]
+        json: bool,
+        
+        /// Don't show a progress bar while analyzing
+        #[clap(long)]
+        no_progress: bool,
+        
+        /// Order of the language breakdown
+        #[clap(long, value_enum, default_value = "size")]
+        sort: SortBy,
+        
+        /// Sort the language breakdown in ascending order
+        #[clap(long, conflicts_with = "sort_desc")]
+        sort_asc: bool,
+        
+        /// Sort the language breakdown in descending order
+        #[clap(long)]
+        sort_desc: bool,
+        
+        /// Only show languages of this type
+        #[clap(long = "type", value_enum)]
+        language_type: Option<TypeFilter>,
+        
+        /// Always show language color swatches
+        #[clap(long, conflicts_with = "no_color")]
+        color: bool,
+        
+        /// Never show language color swatches
+        #[clap(long)]
+        no_color: bool,
+        
+        /// Only analyze files up to <MAX_DEPTH> directories below the path
+        #[clap(long)]
+        max_depth: Option<usize>,
+    },
+    
+    /// Compare the languages of a repository between two commits
+    Diff {
+        /// The older commit
+        old_commit: String,
+        
+        /// The newer commit
+        new_commit: String,
+        
+        /// Path to the repository
+        #[clap(value_parser, default_value = ".")]
+        path: PathBuf,
+        
         /// Use JSON output format
         #[clap(short, long)]
         json: bool,
+    },
+    
+    /// Watch a directory and re-analyze it whenever files change
+    #[cfg(feature = "watch")]
+    Watch {
+        /// Path to the directory
+        #[clap(value_parser)]
+        path: PathBuf,
         
+        /// Poll for changes every <INTERVAL> milliseconds instead of using
+        /// native file system events (e.g. on network file systems)
+        #[clap(long)]
+        interval: Option<u64>,
+    },
+    
+    /// Report extensions shared by several languages and how they are disambiguated
+    Coverage,
+    
+    /// List the interpreters recognized in shebang lines and their languages
+    Interpreters,
+    
+    /// Check a heuristics.yml file for invalid patterns, languages and rules
+    ValidateHeuristics {
+        /// Path to the heuristics.yml file
+        #[clap(value_parser)]
+        file: PathBuf,
+    },
+    
+    /// Print a shell completion script
+    Completions {
+        /// The shell to generate completions for
+        #[clap(value_enum)]
+        shell: Shell,
     },
 }
 
@@ -55,7 +222,7 @@ fn main() {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::File { path } => {
+        Commands::File { path, loc, encoding } => {
             if !path.exists() {
                 eprintln!("Error: File not found: {}", path.display());
                 process::exit(1);
@@ -97,6 +264,20 @@ fn main() {
                     
                     println!("Size: {} bytes", blob.size());
                     
+                    if loc {
+                        println!("Characters: {}", blob.char_count());
+                        println!("Lines: {}", blob.loc());
+                        println!("Non-empty lines: {}", blob.sloc());
+                    }
+                    
+                    if encoding {
+                        match blob.encoding() {
+                            Some((encoding, EncodingConfidence::Certain)) => println!("Encoding: {}", encoding.name()),
+                            Some((encoding, EncodingConfidence::Guessed)) => println!("Encoding: {} (guessed)", encoding.name()),
+                            None => println!("Encoding: Unknown"),
+                        }
+                    }
+                    
                     if let Some(language) = blob.language() {
                         println!("Language: {}", language.name);
                         
@@ -106,10 +287,9 @@ fn main() {
                         
                         println!("Type: {:?}", language.language_type);
                         
-                        if let Some(group) = language.group() {
-                            if group.name != language.name {
-                                println!("Group: {}", group.name);
-                            }
+                        let group = language.resolve_group();
+                        if group.name != language.name {
+                            println!("Group: {}", group.name);
                         }
                     } else {
                         println!("Language: Unknown");
@@ -121,7 +301,13 @@ fn main() {
                 }
             }
         },
-        Commands::Analyze { path, breakdown, percentage, json } => {
+        Commands::Analyze {
+            path, breakdown, show_excluded, percentage, format, json, no_progress, sort, sort_asc, sort_desc, language_type, color, no_color, max_depth,
+        } => {
+            let color = color || (!no_color && std::io::IsTerminal::is_terminal(&std::io::stdout()));
+            let percentage = percentage || sort == SortBy::Percentage;
+            let format = if json { OutputFormat::Json } else { format };
+            
             if !path.exists() {
                 eprintln!("Error: Path not found: {}", path.display());
                 process::exit(1);
@@ -137,34 +323,64 @@ fn main() {
             
             // Create directory analyzer with parallel processing
             let mut analyzer = DirectoryAnalyzer::new(&path);
+            if let Some(max_depth) = max_depth {
+                analyzer = analyzer.with_depth_limit(max_depth);
+            }
             
-            match analyzer.analyze() {
+            match analyze_directory(&mut analyzer, !no_progress) {
                 Ok(stats) => {
-                    if json {
+                    // Restrict the breakdown to the requested language type
+                    let language_breakdown: HashMap<&String, &usize> = stats.language_breakdown.iter()
+                        .filter(|(language, _)| language_type.is_none_or(|filter| filter.matches(language)))
+                        .collect();
+                    
+                    if format == OutputFormat::Json {
                         // Output JSON format
-                        match serde_json::to_string_pretty(&stats.language_breakdown) {
+                        match serde_json::to_string_pretty(&language_breakdown) {
                             Ok(json) => println!("{}", json),
                             Err(err) => {
                                 eprintln!("Error generating JSON: {}", err);
                                 process::exit(1);
                             }
                         }
+                    } else if format == OutputFormat::Toml {
+                        // Output TOML format
+                        let summary = stats.summary();
+                        let report = TomlReport {
+                            languages: language_breakdown.iter().map(|(language, size)| (language.as_str(), **size)).collect(),
+                            summary: TomlSummary {
+                                total_bytes: summary.total_bytes,
+                                primary_language: summary.top_language.as_deref(),
+                            },
+                        };
+                        
+                        match toml::to_string_pretty(&report) {
+                            Ok(toml) => print!("{}", toml),
+                            Err(err) => {
+                                eprintln!("Error generating TOML: {}", err);
+                                process::exit(1);
+                            }
+                        }
                     } else {
                         // Output text format
-                        if let Some(primary) = &stats.language {
-                            println!("Primary language: {}", primary);
+                        let summary = stats.summary();
+                        if let Some(primary) = &summary.top_language {
+                            println!("Primary language: {} {:.1}%", primary, summary.top_language_percentage);
+                            println!(
+                                "{} languages in {} files ({} bytes)",
+                                summary.unique_languages, summary.total_files, summary.total_bytes
+                            );
                         } else {
                             println!("No language detected");
                         }
                         
                         println!("\nLanguage breakdown:");
                         
-                        // Sort languages by size (descending)
-                        let mut languages: Vec<_> = stats.language_breakdown.iter().collect();
-                        languages.sort_by(|a, b| b.1.cmp(a.1));
+                        let mut languages: Vec<_> = language_breakdown.into_iter().collect();
+                        sort_languages(&mut languages, sort, sort_asc, sort_desc);
                         
                         // Calculate total for percentages
-                        let total_size = stats.total_size;
+                        let total_size: usize = languages.iter().map(|(_, size)| **size).sum();
                         
                         for (language, size) in languages {
                             if percentage {
@@ -180,17 +396,29 @@ fn main() {
                             println!("\nFile breakdown:");
                             
                             // Sort languages alphabetically
-                            let mut languages: Vec<_> = stats.file_breakdown.keys().collect();
-                            languages.sort();
+                            let mut languages: Vec<_> = stats.files_by_language()
+                                .into_iter()
+                                .filter(|(language, _)| language_type.is_none_or(|filter| filter.matches(language)))
+                                .collect();
+                            languages.sort_by_key(|(language, _)| *language);
                             
-                            for language in languages {
-                                println!("\n{}:", language);
+                            for (language, files) in languages {
+                                match Language::find_by_name(language).filter(|_| color) {
+                                    Some(lang) => println!("\n{} {}:", format_colored(lang, "█"), language),
+                                    None => println!("\n{}:", language),
+                                }
                                 
-                                let files = &stats.file_breakdown[language];
                                 for file in files {
                                     println!("  {}", file);
                                 }
                             }
+                            
+                            if show_excluded && !stats.excluded_files.is_empty() {
+                                println!("\nExcluded files:");
+                                for (file, reason) in &stats.excluded_files {
+                                    println!("  {} ({})", file, reason.description());
+                                }
+                            }
                         }
                     }
                 },
@@ -199,6 +427,285 @@ fn main() {
                     process::exit(1);
                 }
             }
+        },
+        Commands::Diff { old_commit, new_commit, path, json } => {
+            let diff = match diff_commits(&path, &old_commit, &new_commit) {
+                Ok(diff) => diff,
+                Err(err) => {
+                    eprintln!("Error comparing commits: {}", err);
+                    process::exit(1);
+                }
+            };
+            
+            if json {
+                match serde_json::to_string_pretty(&diff) {
+                    Ok(json) => println!("{}", json),
+                    Err(err) => {
+                        eprintln!("Error generating JSON: {}", err);
+                        process::exit(1);
+                    }
+                }
+            } else if diff.is_empty() {
+                println!("No language changes");
+            } else {
+                let color = std::io::IsTerminal::is_terminal(&std::io::stdout());
+                
+                println!("{:<30} {:>12} {:>12} {:>12}", "Language", "Old", "New", "Change");
+                print_changes(&diff.grown, "32", color);
+                print_changes(&diff.shrunk, "31", color);
+                print_changes(&diff.added, "36", color);
+                print_changes(&diff.removed, "90", color);
+            }
+        },
+        #[cfg(feature = "watch")]
+        Commands::Watch { path, interval } => {
+            if !path.exists() {
+                eprintln!("Error: Path not found: {}", path.display());
+                process::exit(1);
+            }
+            
+            if let Err(err) = watch_directory(&path, interval) {
+                eprintln!("Error watching directory: {}", err);
+                process::exit(1);
+            }
+        },
+        Commands::Coverage => {
+            let samples = match linguist::data::samples::load_samples() {
+                Ok(samples) => samples,
+                Err(err) => {
+                    eprintln!("Error loading samples: {}", err);
+                    process::exit(1);
+                }
+            };
+            let covered = match Heuristics::new_from_bundled() {
+                Ok(heuristics) => heuristics.coverage(),
+                Err(err) => {
+                    eprintln!("Error loading heuristics: {}", err);
+                    process::exit(1);
+                }
+            };
+            let ambiguous = Language::ambiguous_extensions();
+            
+            println!("{:<16} | {:<50} | {:<10} | Sample Count", "Extension", "Languages", "Strategy");
+            
+            let mut classifier_only = 0;
+            for (extension, languages) in &ambiguous {
+                let strategy = if covered.contains(extension) {
+                    "Heuristics"
+                } else {
+                    classifier_only += 1;
+                    "Classifier"
+                };
+                
+                let names: Vec<&str> = languages.iter().map(|l| l.name.as_str()).collect();
+                let sample_count: usize = languages.iter()
+                    .filter_map(|language| samples.get(&language.name))
+                    .flatten()
+                    .filter(|sample| sample.path.to_string_lossy().to_lowercase().ends_with(extension.as_str()))
+                    .count();
+                
+                println!("{:<16} | {:<50} | {:<10} | {}", extension, names.join(", "), strategy, sample_count);
+            }
+            
+            println!(
+                "\n{} of {} ambiguous extensions rely solely on the classifier",
+                classifier_only, ambiguous.len()
+            );
+        },
+        Commands::Interpreters => {
+            let mut interpreters: Vec<String> = Shebang::supported_interpreters().into_iter().collect();
+            interpreters.sort();
+            
+            for interpreter in interpreters {
+                let names: Vec<&str> = Language::find_by_interpreter(&interpreter)
+                    .iter()
+                    .map(|language| language.name.as_str())
+                    .collect();
+                println!("{:<20} {}", interpreter, names.join(", "));
+            }
+        },
+        Commands::ValidateHeuristics { file } => {
+            match heuristics::validate_yaml(&file) {
+                Ok(errors) if errors.is_empty() => {
+                    println!("No problems found in {}", file.display());
+                },
+                Ok(errors) => {
+                    for error in &errors {
+                        println!("{}", error);
+                    }
+                    eprintln!("{} problem(s) found in {}", errors.len(), file.display());
+                    process::exit(1);
+                },
+                Err(err) => {
+                    eprintln!("Error reading heuristics file: {}", err);
+                    process::exit(1);
+                }
+            }
+        },
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "linguist", &mut std::io::stdout());
         }
     }
-}
\ No newline at end of file
+}
+
+/// Compute the language changes of a repository between two revisions
+fn diff_commits(path: &Path, old_rev: &str, new_rev: &str) -> linguist::Result<LanguageStatsDiff> {
+    // Resolve short SHAs and refs to full commit IDs
+    let old_commit = Repository::resolve_ref(path, old_rev)?;
+    let new_commit = Repository::resolve_ref(path, new_rev)?;
+    
+    let mut old_repo = Repository::new(path, &old_commit, None)?;
+    let old_stats = old_repo.stats()?;
+    
+    let mut new_repo = Repository::incremental(path, &new_commit, &old_commit, old_repo.file_stats_cache()?, None)?;
+    let new_stats = new_repo.stats()?;
+    
+    Ok(old_stats.diff(&new_stats))
+}
+
+/// Print one row per language change, optionally colored with an ANSI SGR code
+fn print_changes(changes: &[LanguageChange], ansi_code: &str, color: bool) {
+    for change in changes {
+        let delta = change.new_size as i64 - change.old_size as i64;
+        let row = format!("{:<30} {:>12} {:>12} {:>+12}", change.language, change.old_size, change.new_size, delta);
+        
+        if color {
+            println!("\x1b[{}m{}\x1b[0m", ansi_code, row);
+        } else {
+            println!("{}", row);
+        }
+    }
+}
+
+/// Analyze a directory and re-analyze it after every batch of file changes
+///
+/// Runs until the watcher fails.
+#[cfg(feature = "watch")]
+fn watch_directory(path: &Path, interval: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use notify::{PollWatcher, RecursiveMode, Watcher};
+    
+    let (tx, rx) = mpsc::channel();
+    let mut watcher: Box<dyn Watcher> = match interval {
+        Some(ms) => {
+            let config = notify::Config::default().with_poll_interval(Duration::from_millis(ms));
+            Box::new(PollWatcher::new(tx, config)?)
+        },
+        None => Box::new(notify::recommended_watcher(tx)?),
+    };
+    watcher.watch(path, RecursiveMode::Recursive)?;
+    
+    let mut analyzer = DirectoryAnalyzer::new(path);
+    print_language_sizes(&analyzer.analyze()?);
+    
+    loop {
+        match rx.recv()? {
+            Ok(event) if is_content_change(&event) => {},
+            Ok(_) => continue,
+            Err(err) => {
+                eprintln!("Watch error: {}", err);
+                continue;
+            }
+        }
+        
+        // Batch rapid changes until the directory has been quiet for a while
+        while rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+        
+        println!("\nChanges detected, re-analyzing...\n");
+        analyzer.reset();
+        print_language_sizes(&analyzer.analyze()?);
+    }
+}
+
+/// Check whether a file system event changes file contents outside `.git`
+#[cfg(feature = "watch")]
+fn is_content_change(event: &notify::Event) -> bool {
+    use notify::EventKind;
+    
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+        && event.paths.iter().any(|path| !path.components().any(|c| c.as_os_str() == ".git"))
+}
+
+/// Print the language breakdown, largest first
+#[cfg(feature = "watch")]
+fn print_language_sizes(stats: &LanguageStats) {
+    let mut languages: Vec<_> = stats.language_breakdown.iter().collect();
+    sort_languages(&mut languages, SortBy::Size, false, false);
+    
+    for (language, size) in languages {
+        let percent = (*size as f64 / stats.total_size as f64) * 100.0;
+        println!("{}: {} bytes ({:.1}%)", language, size, percent);
+    }
+}
+
+/// Sort the language breakdown before printing
+///
+/// Names sort ascending and sizes descending unless a direction is forced.
+fn sort_languages(languages: &mut [(&String, &usize)], sort: SortBy, ascending: bool, descending: bool) {
+    match sort {
+        SortBy::Name => languages.sort_by(|a, b| a.0.cmp(b.0)),
+        SortBy::Size | SortBy::Percentage => languages.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0))),
+    }
+    
+    let default_ascending = sort == SortBy::Name;
+    if (ascending && !default_ascending) || (descending && default_ascending) {
+        languages.reverse();
+    }
+}
+
+/// Color text with a language's color using ANSI escape codes
+///
+/// Uses 24-bit color when the terminal advertises it through `COLORTERM`,
+/// and the nearest color of the 256-color palette otherwise. Text is
+/// returned unchanged for languages without a color.
+fn format_colored(language: &Language, text: &str) -> String {
+    let Some((r, g, b)) = language.color_as_rgb() else {
+        return text.to_string();
+    };
+    
+    let truecolor = std::env::var("COLORTERM")
+        .map(|value| value == "truecolor" || value == "24bit")
+        .unwrap_or(false);
+    
+    if truecolor {
+        format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, text)
+    } else {
+        // Index into the 6x6x6 color cube of the 256-color palette
+        let level = |c: u8| (c as u16 * 5 + 127) / 255;
+        let index = 16 + 36 * level(r) + 6 * level(g) + level(b);
+        format!("\x1b[38;5;{}m{}\x1b[0m", index, text)
+    }
+}
+
+/// Analyze a directory, showing a progress bar on interactive terminals
+#[cfg(feature = "cli-progress")]
+fn analyze_directory(analyzer: &mut DirectoryAnalyzer, show_progress: bool) -> linguist::Result<LanguageStats> {
+    use std::io::IsTerminal;
+    use indicatif::{ProgressBar, ProgressStyle};
+    
+    if !show_progress || !std::io::stderr().is_terminal() {
+        return analyzer.analyze();
+    }
+    
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::with_template("[{bar:40}] {pos}/{len} files ({elapsed} elapsed, ~{eta} remaining)")
+            .expect("valid progress template")
+            .progress_chars("=> ")
+    );
+    
+    let result = analyzer.analyze_with_progress(|_, total| {
+        bar.set_length(total as u64);
+        bar.inc(1);
+    });
+    
+    bar.finish_and_clear();
+    result
+}
+
+/// Analyze a directory
+#[cfg(not(feature = "cli-progress"))]
+fn analyze_directory(analyzer: &mut DirectoryAnalyzer, _show_progress: bool) -> linguist::Result<LanguageStats> {
+    analyzer.analyze()
+}
@@ -5,21 +5,30 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-use git2::{Repository as GitRepository, Tree, Oid, ObjectType, FileMode};
+use git2::{Repository as GitRepository, Tree, TreeWalkMode, TreeWalkResult, Oid, ObjectType, FileMode};
 use rayon::prelude::*;
+use sha2::{Digest, Sha256};
 use dashmap::DashMap;
 
-use crate::blob::{BlobHelper, LazyBlob, FileBlob};
+use crate::blob::{include_stats, BlobHelper, InclusionReason, LazyBlob, FileBlob};
+use crate::language::Language;
 use crate::{Error, Result};
 
 // Maximum repository tree size to consider for analysis
 const MAX_TREE_SIZE: usize = 100_000;
 
+// Maximum bytes of each file read for detection by DirectoryAnalyzer
+const MAX_READ_BYTES: usize = 128 * 1024;
+
 /// Type alias for the cache mapping of filename to (language, size)
 type FileStatsCache = DashMap<String, (String, usize)>;
 
+/// Type alias for `.gitattributes` overrides with their compiled path patterns, in file order
+pub(crate) type GitAttributeRules = Vec<(regex::Regex, GitAttributeOverride)>;
+
 /// Repository analysis results
 #[derive(Debug, Clone)]
 pub struct LanguageStats {
@@ -34,6 +43,530 @@ pub struct LanguageStats {
     
     /// Breakdown of files by language
     pub file_breakdown: HashMap<String, Vec<String>>,
+    
+    /// Size in bytes of each detected file
+    pub file_sizes: HashMap<String, usize>,
+    
+    /// Paths of git submodules, which are excluded from the statistics
+    pub submodule_paths: Vec<String>,
+    
+    /// Number of files skipped for exceeding the maximum file size
+    pub skipped_files: usize,
+    
+    /// Files left out of the statistics and the reason why, sorted by path
+    pub excluded_files: Vec<(String, ExclusionReason)>,
+}
+
+/// Reason a file was left out of the language statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    /// The file content is binary
+    Binary,
+    
+    /// The file is vendored third-party code
+    Vendored,
+    
+    /// The file is generated
+    Generated,
+    
+    /// The file is documentation
+    Documentation,
+    
+    /// The file exceeds the maximum file size
+    TooBig,
+    
+    /// No language could be detected for the file
+    Unknown,
+    
+    /// The file is a symbolic link
+    Symlink,
+}
+
+impl ExclusionReason {
+    /// Get a short description of the reason
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - The description
+    pub fn description(&self) -> &'static str {
+        match self {
+            ExclusionReason::Binary => "binary",
+            ExclusionReason::Vendored => "vendored",
+            ExclusionReason::Generated => "generated",
+            ExclusionReason::Documentation => "documentation",
+            ExclusionReason::TooBig => "too big",
+            ExclusionReason::Unknown => "unknown language",
+            ExclusionReason::Symlink => "symlink",
+        }
+    }
+}
+
+/// Headline figures for a set of language statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummaryStats {
+    /// Number of detected files
+    pub total_files: usize,
+    
+    /// Total size of detected files in bytes
+    pub total_bytes: usize,
+    
+    /// Number of distinct languages
+    pub unique_languages: usize,
+    
+    /// Primary language
+    pub top_language: Option<String>,
+    
+    /// Share of the total size taken by the primary language (0-100)
+    pub top_language_percentage: f64,
+}
+
+/// Change in size of a single language between two analyses
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct LanguageChange {
+    /// Language name
+    pub language: String,
+    
+    /// Size in bytes before the change (0 if the language is new)
+    pub old_size: usize,
+    
+    /// Size in bytes after the change (0 if the language was removed)
+    pub new_size: usize,
+}
+
+/// Differences in language breakdown between two analyses
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct LanguageStatsDiff {
+    /// Languages whose size increased
+    pub grown: Vec<LanguageChange>,
+    
+    /// Languages whose size decreased
+    pub shrunk: Vec<LanguageChange>,
+    
+    /// Languages that only appear in the newer analysis
+    pub added: Vec<LanguageChange>,
+    
+    /// Languages that only appear in the older analysis
+    pub removed: Vec<LanguageChange>,
+}
+
+/// Read-only view of the files detected for each language
+///
+/// Returned by `LanguageStats::files_by_language`. Iterating yields
+/// `(language, files)` pairs in no particular order.
+#[derive(Debug, Clone, Copy)]
+pub struct LanguageFileBreakdown<'a> {
+    files: &'a HashMap<String, Vec<String>>,
+}
+
+impl<'a> LanguageFileBreakdown<'a> {
+    /// Get the files detected as a language
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The language name
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&[String]>` - The file paths, if any file was detected as the language
+    pub fn for_language(&self, name: &str) -> Option<&'a [String]> {
+        self.files.get(name).map(|files| files.as_slice())
+    }
+    
+    /// Get the number of languages with at least one file
+    pub fn language_count(&self) -> usize {
+        self.files.len()
+    }
+}
+
+impl<'a> IntoIterator for LanguageFileBreakdown<'a> {
+    type Item = (&'a str, &'a [String]);
+    type IntoIter = std::iter::Map<
+        std::collections::hash_map::Iter<'a, String, Vec<String>>,
+        fn((&'a String, &'a Vec<String>)) -> (&'a str, &'a [String]),
+    >;
+    
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.iter().map(|(language, files)| (language.as_str(), files.as_slice()))
+    }
+}
+
+impl LanguageStatsDiff {
+    /// Check whether the two analyses have the same language breakdown
+    pub fn is_empty(&self) -> bool {
+        self.grown.is_empty() && self.shrunk.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl LanguageStats {
+    /// Compare these statistics with a newer analysis
+    ///
+    /// # Arguments
+    ///
+    /// * `newer` - The statistics to compare against
+    ///
+    /// # Returns
+    ///
+    /// * `LanguageStatsDiff` - The changes, each list sorted by language name
+    pub fn diff(&self, newer: &LanguageStats) -> LanguageStatsDiff {
+        let mut languages: Vec<&String> = self.language_breakdown.keys()
+            .chain(newer.language_breakdown.keys())
+            .collect();
+        languages.sort();
+        languages.dedup();
+        
+        let mut diff = LanguageStatsDiff::default();
+        for language in languages {
+            let old_size = self.language_breakdown.get(language).copied();
+            let new_size = newer.language_breakdown.get(language).copied();
+            
+            let change = LanguageChange {
+                language: language.clone(),
+                old_size: old_size.unwrap_or(0),
+                new_size: new_size.unwrap_or(0),
+            };
+            
+            match (old_size, new_size) {
+                (None, Some(_)) => diff.added.push(change),
+                (Some(_), None) => diff.removed.push(change),
+                (Some(old), Some(new)) if new > old => diff.grown.push(change),
+                (Some(old), Some(new)) if new < old => diff.shrunk.push(change),
+                _ => {}
+            }
+        }
+        
+        diff
+    }
+    
+    /// Get the reason a file was left out of the statistics
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file path
+    ///
+    /// # Returns
+    ///
+    /// * `Option<ExclusionReason>` - The reason, or None if the file wasn't excluded
+    pub fn excluded_reason(&self, path: &str) -> Option<ExclusionReason> {
+        self.excluded_files
+            .binary_search_by(|(excluded, _)| excluded.as_str().cmp(path))
+            .ok()
+            .map(|index| self.excluded_files[index].1)
+    }
+    
+    /// Get the files detected for each language
+    ///
+    /// # Returns
+    ///
+    /// * `LanguageFileBreakdown` - A view over `file_breakdown`
+    pub fn files_by_language(&self) -> LanguageFileBreakdown<'_> {
+        LanguageFileBreakdown { files: &self.file_breakdown }
+    }
+    
+    /// Summarize the statistics for quick display (e.g. "Rust 94.3%")
+    ///
+    /// # Returns
+    ///
+    /// * `SummaryStats` - The summary
+    pub fn summary(&self) -> SummaryStats {
+        let top_language_percentage = match &self.language {
+            Some(language) if self.total_size > 0 => {
+                let size = self.language_breakdown.get(language).copied().unwrap_or(0);
+                (size as f64 / self.total_size as f64) * 100.0
+            },
+            _ => 0.0,
+        };
+        
+        SummaryStats {
+            total_files: self.file_breakdown.values().map(|files| files.len()).sum(),
+            total_bytes: self.total_size,
+            unique_languages: self.language_breakdown.len(),
+            top_language: self.language.clone(),
+            top_language_percentage,
+        }
+    }
+    
+    /// Convert the language breakdown into chart data for web dashboards
+    ///
+    /// Produces an array of `{"label": "Rust", "value": 0.943, "color": "#dea584"}`
+    /// objects, where `value` is the language's share of the total size,
+    /// sorted by share descending. Languages without a color get one
+    /// derived from a hash of their name, so it is stable across runs.
+    ///
+    /// # Returns
+    ///
+    /// * `serde_json::Value` - The chart data, ready for Chart.js, D3.js or Svelte chart libraries
+    pub fn to_chart_data(&self) -> serde_json::Value {
+        let mut languages: Vec<(&String, &usize)> = self.language_breakdown.iter().collect();
+        languages.sort_by(|(a_name, a_size), (b_name, b_size)| b_size.cmp(a_size).then_with(|| a_name.cmp(b_name)));
+        
+        let entries = languages.into_iter()
+            .map(|(name, &size)| {
+                let value = if self.total_size > 0 {
+                    size as f64 / self.total_size as f64
+                } else {
+                    0.0
+                };
+                let color = Language::find_by_name(name)
+                    .and_then(|language| language.color.clone())
+                    .unwrap_or_else(|| fallback_color(name));
+                
+                serde_json::json!({
+                    "label": name,
+                    "value": value,
+                    "color": color,
+                })
+            })
+            .collect();
+        
+        serde_json::Value::Array(entries)
+    }
+    
+    /// Group detected files into logarithmic size buckets
+    ///
+    /// Bucket upper bounds are powers of two (1B, 2B, 4B, ...) up to the
+    /// largest file size. A file falls into the first bucket whose upper
+    /// bound is at least its size. When more than `buckets` buckets would
+    /// be needed, the last bucket also holds all larger files.
+    ///
+    /// # Arguments
+    ///
+    /// * `buckets` - Maximum number of buckets
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(usize, usize)>` - `(upper_bound_bytes, count)` pairs, sorted by upper bound
+    pub fn file_size_histogram(&self, buckets: usize) -> Vec<(usize, usize)> {
+        let max_size = match self.file_sizes.values().max() {
+            Some(&max_size) if buckets > 0 => max_size,
+            _ => return Vec::new(),
+        };
+        
+        let bucket_index = |size: usize| size.max(1).next_power_of_two().trailing_zeros() as usize;
+        let count = (bucket_index(max_size) + 1).min(buckets);
+        
+        let mut histogram: Vec<(usize, usize)> = (0..count)
+            .map(|i| (1usize << i, 0))
+            .collect();
+        
+        // The last bucket always covers the largest file
+        histogram[count - 1].0 = max_size.max(1).next_power_of_two();
+        
+        for &size in self.file_sizes.values() {
+            histogram[bucket_index(size).min(count - 1)].1 += 1;
+        }
+        
+        histogram
+    }
+}
+
+/// Derive a stable `#rrggbb` color from a language name
+fn fallback_color(name: &str) -> String {
+    let digest = Sha256::digest(name.as_bytes());
+    format!("#{:02x}{:02x}{:02x}", digest[0], digest[1], digest[2])
+}
+
+/// Summary of a commit, as returned by `Repository::list_commits`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// The commit ID
+    pub oid: String,
+    
+    /// The full commit message
+    pub message: String,
+    
+    /// Commit time in seconds since the Unix epoch
+    pub timestamp: i64,
+    
+    /// The author name
+    pub author: String,
+}
+
+/// An entry of a commit's tree, as returned by `Repository::walk_tree_entries`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// The repository-relative path
+    pub path: String,
+    
+    /// The ID of the object the entry points to
+    pub oid: Oid,
+    
+    /// The file mode, e.g. `0o100644` for a regular file
+    pub mode: i32,
+    
+    /// The kind of object: a blob, a tree, or a commit for submodules
+    pub kind: ObjectType,
+}
+
+/// Linguist attribute overrides declared for a path pattern in `.gitattributes`
+///
+/// Each field is `None` when the attribute is not mentioned, so that
+/// detection falls back to the built-in behavior.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitAttributeOverride {
+    /// Language forced by `linguist-language=<name>`
+    pub language: Option<String>,
+    
+    /// Set by `linguist-vendored` / `-linguist-vendored`
+    pub vendored: Option<bool>,
+    
+    /// Set by `linguist-generated` / `-linguist-generated`
+    pub generated: Option<bool>,
+    
+    /// Set by `linguist-documentation` / `-linguist-documentation`
+    pub documentation: Option<bool>,
+    
+    /// Set by `linguist-detectable` / `-linguist-detectable`
+    pub detectable: Option<bool>,
+}
+
+impl GitAttributeOverride {
+    /// Parse the content of a `.gitattributes` file
+    ///
+    /// Only lines with at least one `linguist-*` attribute are kept. As in
+    /// git, when several lines match a path, later lines take precedence.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The `.gitattributes` file content
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, GitAttributeOverride)>` - Path patterns and their overrides, in file order
+    pub fn parse(content: &str) -> Vec<(String, GitAttributeOverride)> {
+        let mut attributes = Vec::new();
+        
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            let mut parts = line.split_whitespace();
+            let pattern = match parts.next() {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+            
+            let mut attribute = GitAttributeOverride::default();
+            let mut changed = false;
+            
+            for attr in parts {
+                // `attr` sets, `-attr` unsets, `!attr` leaves the attribute unspecified
+                let (name, value) = if let Some(name) = attr.strip_prefix('-') {
+                    (name, Some("false"))
+                } else if let Some(name) = attr.strip_prefix('!') {
+                    (name, None)
+                } else if let Some((name, value)) = attr.split_once('=') {
+                    (name, Some(value))
+                } else {
+                    (attr, Some("true"))
+                };
+                
+                let flag = value.map(|value| value != "false");
+                
+                match name {
+                    "linguist-language" => attribute.language = value.map(String::from),
+                    "linguist-vendored" => attribute.vendored = flag,
+                    "linguist-generated" => attribute.generated = flag,
+                    "linguist-documentation" => attribute.documentation = flag,
+                    "linguist-detectable" => attribute.detectable = flag,
+                    _ => continue,
+                }
+                
+                changed = true;
+            }
+            
+            if changed {
+                attributes.push((pattern.to_string(), attribute));
+            }
+        }
+        
+        attributes
+    }
+    
+    /// Parse a `.gitattributes` file and compile its path patterns
+    ///
+    /// Lines with invalid patterns are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The `.gitattributes` file content
+    ///
+    /// # Returns
+    ///
+    /// * `GitAttributeRules` - Overrides with their compiled path patterns, in file order
+    pub(crate) fn compile(content: &str) -> GitAttributeRules {
+        Self::parse(content)
+            .into_iter()
+            .filter_map(|(pattern, attribute)| {
+                Self::compile_pattern(&pattern).map(|regex| (regex, attribute))
+            })
+            .collect()
+    }
+    
+    /// Merge the `.gitattributes` overrides that apply to a path
+    ///
+    /// As in git, when several lines set the same attribute, the last
+    /// matching line in the file wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `gitattributes` - Linguist overrides with their compiled path patterns, in file order
+    /// * `path` - The repository-relative path
+    ///
+    /// # Returns
+    ///
+    /// * `GitAttributeOverride` - The merged overrides
+    pub(crate) fn for_path(gitattributes: &[(regex::Regex, GitAttributeOverride)], path: &str) -> Self {
+        let matching = gitattributes.iter()
+            .filter(|(regex, _)| regex.is_match(path));
+        
+        let mut merged = GitAttributeOverride::default();
+        for (_, attribute) in matching {
+            if attribute.language.is_some() {
+                merged.language = attribute.language.clone();
+            }
+            merged.vendored = attribute.vendored.or(merged.vendored);
+            merged.generated = attribute.generated.or(merged.generated);
+            merged.documentation = attribute.documentation.or(merged.documentation);
+            merged.detectable = attribute.detectable.or(merged.detectable);
+        }
+        
+        merged
+    }
+    
+    /// Check if a `.gitattributes` pattern matches a path
+    ///
+    /// Patterns without a slash match the file's basename, others match the
+    /// full path relative to the repository root.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The gitattributes pattern
+    /// * `path` - The repository-relative path
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the pattern matches
+    pub fn pattern_matches(pattern: &str, path: &str) -> bool {
+        Self::compile_pattern(pattern)
+            .map(|regex| regex.is_match(path))
+            .unwrap_or(false)
+    }
+    
+    /// Compile a `.gitattributes` pattern into a regular expression
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The gitattributes pattern
+    ///
+    /// # Returns
+    ///
+    /// * `Option<regex::Regex>` - The compiled pattern, or None if it is invalid
+    fn compile_pattern(pattern: &str) -> Option<regex::Regex> {
+        regex::Regex::new(&crate::vendor::glob_to_regex(pattern)).ok()
+    }
 }
 
 /// Repository analysis functionality
@@ -55,6 +588,15 @@ pub struct Repository {
     
     /// Analysis cache
     cache: Option<FileStatsCache>,
+    
+    /// Linguist overrides read from `.gitattributes`, in file order
+    gitattributes: GitAttributeRules,
+    
+    /// Size in bytes above which files are skipped, None for unlimited
+    max_file_size: Option<usize>,
+    
+    /// Files excluded from the statistics in the last scan, with the reason
+    excluded_paths: DashMap<String, ExclusionReason>,
 }
 
 impl Repository {
@@ -80,6 +622,9 @@ impl Repository {
             old_commit_oid: None,
             old_stats: None,
             cache: None,
+            gitattributes: Vec::new(),
+            max_file_size: None,
+            excluded_paths: DashMap::new(),
         })
     }
     
@@ -115,9 +660,125 @@ impl Repository {
             old_commit_oid: Some(old_commit_oid),
             old_stats: Some(old_stats),
             cache: None,
+            gitattributes: Vec::new(),
+            max_file_size: None,
+            excluded_paths: DashMap::new(),
         })
     }
     
+    /// Skip files larger than a maximum size during analysis
+    ///
+    /// Skipped files are left out of the statistics and counted in
+    /// `LanguageStats::skipped_files`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum file size in bytes
+    ///
+    /// # Returns
+    ///
+    /// * `Repository` - The updated repository
+    pub fn with_max_file_size(mut self, max: usize) -> Self {
+        self.max_file_size = Some(max);
+        self
+    }
+    
+    /// Resolve a ref or revision to a commit SHA
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_path` - Path to the Git repository
+    /// * `refname` - A ref, branch, tag or (short) commit ID, e.g. `HEAD` or `main`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The full hex SHA of the commit
+    pub fn resolve_ref(repo_path: &Path, refname: &str) -> Result<String> {
+        let repo = GitRepository::open(repo_path)?;
+        let commit = repo.revparse_single(refname)?.peel_to_commit()?;
+        Ok(commit.id().to_string())
+    }
+    
+    /// Resolve `HEAD` to a commit SHA
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_path` - Path to the Git repository
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The full hex SHA of the commit at HEAD
+    pub fn head(repo_path: &Path) -> Result<String> {
+        Self::resolve_ref(repo_path, "HEAD")
+    }
+    
+    /// Check whether a repository has no commits yet
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_path` - Path to the Git repository
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - True if the repository has no commits
+    pub fn is_empty(repo_path: &Path) -> Result<bool> {
+        Ok(GitRepository::open(repo_path)?.is_empty()?)
+    }
+    
+    /// List the most recent commits on the current branch
+    ///
+    /// Follows first parents starting at HEAD.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_path` - Path to the Git repository
+    /// * `max` - Maximum number of commits to return
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<CommitInfo>>` - The commits, newest first
+    pub fn list_commits(repo_path: &Path, max: usize) -> Result<Vec<CommitInfo>> {
+        let mut commits = Vec::new();
+        if max == 0 {
+            return Ok(commits);
+        }
+        
+        let repo = GitRepository::open(repo_path)?;
+        let mut commit = repo.head()?.peel_to_commit()?;
+        
+        loop {
+            commits.push(CommitInfo {
+                oid: commit.id().to_string(),
+                message: commit.message().unwrap_or_default().to_string(),
+                timestamp: commit.time().seconds(),
+                author: commit.author().name().unwrap_or_default().to_string(),
+            });
+            
+            if commits.len() >= max {
+                break;
+            }
+            
+            commit = match commit.parent(0) {
+                Ok(parent) => parent,
+                Err(_) => break, // Reached the root commit
+            };
+        }
+        
+        Ok(commits)
+    }
+    
+    /// Get a copy of the per-file statistics
+    ///
+    /// The copy can be passed to `incremental` to analyze a later commit
+    /// without rescanning unchanged files.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FileStatsCache>` - Mapping of file path to (language, size)
+    pub fn file_stats_cache(&mut self) -> Result<FileStatsCache> {
+        Ok(self.get_cache()?.clone())
+    }
+    
     /// Load existing analysis results
     ///
     /// # Arguments
@@ -215,15 +876,187 @@ impl Repository {
         let total_size = self.size()?;
         let language = self.language()?;
         let file_breakdown = self.breakdown_by_file()?;
+        let file_sizes = self.get_cache()?.iter()
+            .map(|entry| (entry.key().clone(), entry.value().1))
+            .collect();
+        let submodule_paths = self.submodule_paths()?;
+        let excluded_files = self.excluded_files("");
+        let skipped_files = excluded_files.iter()
+            .filter(|(_, reason)| *reason == ExclusionReason::TooBig)
+            .count();
         
         Ok(LanguageStats {
             language_breakdown,
             total_size,
             language,
             file_breakdown,
+            file_sizes,
+            submodule_paths,
+            skipped_files,
+            excluded_files,
         })
     }
     
+    /// Get every entry of the commit's tree, recursing into subtrees
+    ///
+    /// Entries are listed in pre-order: a directory comes before the
+    /// entries it contains. Symlinks and submodules are included, so
+    /// callers can filter on `mode` and `kind` themselves.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<TreeEntry>>` - The tree entries, empty for a repository without commits
+    pub fn walk_tree_entries(&self) -> Result<Vec<TreeEntry>> {
+        if self.repo.is_empty()? {
+            return Ok(Vec::new());
+        }
+        
+        let tree = self.get_tree(self.commit_oid)?;
+        let mut entries = Vec::new();
+        
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            if let Some(kind) = entry.kind() {
+                entries.push(TreeEntry {
+                    path: format!("{}{}", root, entry.name().unwrap_or_default()),
+                    oid: entry.id(),
+                    mode: entry.filemode(),
+                    kind,
+                });
+            }
+            TreeWalkResult::Ok
+        })?;
+        
+        Ok(entries)
+    }
+    
+    /// Get the paths of all git submodules in the commit's tree
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>>` - Repository-relative submodule paths, sorted
+    pub fn submodule_paths(&self) -> Result<Vec<String>> {
+        if self.repo.is_empty()? {
+            return Ok(Vec::new());
+        }
+        
+        let tree = self.get_tree(self.commit_oid)?;
+        let mut paths = Vec::new();
+        
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() == Some(ObjectType::Commit) {
+                paths.push(format!("{}{}", root, entry.name().unwrap_or_default()));
+            }
+            TreeWalkResult::Ok
+        })?;
+        
+        paths.sort();
+        Ok(paths)
+    }
+    
+    /// Get the language of a single file in the repository
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The repository-relative file path
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<String>>` - The language name, or None if the file was
+    ///   not found or excluded from language stats
+    pub fn file_language(&mut self, path: &str) -> Result<Option<String>> {
+        let path = path.trim_start_matches('/');
+        let cache = self.get_cache()?;
+        
+        Ok(cache.get(path).map(|entry| entry.value().0.clone()))
+    }
+    
+    /// Get the language statistics for a subdirectory of the repository
+    ///
+    /// The full repository analysis is cached, so repeated calls only
+    /// filter the cached results.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The directory path, e.g. `services/auth/`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LanguageStats>` - The language statistics for files under `prefix`
+    pub fn analyze_subtree(&mut self, prefix: &str) -> Result<LanguageStats> {
+        // Normalize to a repository-relative directory prefix
+        let prefix = prefix.trim_start_matches('/');
+        let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+            prefix.to_string()
+        } else {
+            format!("{}/", prefix)
+        };
+        
+        let cache = self.get_cache()?;
+        
+        let mut language_breakdown = HashMap::new();
+        let mut file_breakdown = HashMap::new();
+        let mut file_sizes = HashMap::new();
+        for entry in cache.iter().filter(|entry| entry.key().starts_with(&prefix)) {
+            let (language, size) = entry.value();
+            *language_breakdown.entry(language.clone()).or_insert(0) += size;
+            file_breakdown.entry(language.clone())
+                .or_insert_with(Vec::new)
+                .push(entry.key().clone());
+            file_sizes.insert(entry.key().clone(), *size);
+        }
+        
+        // Sort filenames for consistent output
+        for files in file_breakdown.values_mut() {
+            files.sort();
+        }
+        
+        let total_size = language_breakdown.values().sum();
+        let language = language_breakdown.iter()
+            .max_by_key(|&(_, size)| size)
+            .map(|(lang, _)| lang.clone());
+        
+        let submodule_paths = self.submodule_paths()?
+            .into_iter()
+            .filter(|path| path.starts_with(&prefix))
+            .collect();
+        let excluded_files = self.excluded_files(&prefix);
+        let skipped_files = excluded_files.iter()
+            .filter(|(_, reason)| *reason == ExclusionReason::TooBig)
+            .count();
+        
+        Ok(LanguageStats {
+            language_breakdown,
+            total_size,
+            language,
+            file_breakdown,
+            file_sizes,
+            submodule_paths,
+            skipped_files,
+            excluded_files,
+        })
+    }
+    
+    /// Get the files excluded from the statistics in the last scan
+    ///
+    /// An incremental analysis only scans the files changed since the
+    /// previous commit, so only those can be reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Only include paths starting with this prefix
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, ExclusionReason)>` - Excluded paths and their reason, sorted by path
+    fn excluded_files(&self, prefix: &str) -> Vec<(String, ExclusionReason)> {
+        let mut excluded: Vec<_> = self.excluded_paths.iter()
+            .filter(|entry| entry.key().starts_with(prefix))
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        excluded.sort();
+        excluded
+    }
+    
     /// Get the analysis cache
     ///
     /// # Returns
@@ -251,7 +1084,12 @@ impl Repository {
     /// # Returns
     ///
     /// * `Result<FileStatsCache>` - The computed file stats
-    fn compute_stats(&self) -> Result<FileStatsCache> {
+    fn compute_stats(&mut self) -> Result<FileStatsCache> {
+        // A repository without commits has no tree to analyze
+        if self.repo.is_empty()? {
+            return Ok(DashMap::new());
+        }
+        
         // Check if tree is too large
         let tree_size = self.get_tree_size(self.commit_oid)?;
         if tree_size >= self.max_tree_size {
@@ -260,6 +1098,7 @@ impl Repository {
         
         // Set up attribute source for .gitattributes
         self.set_attribute_source(self.commit_oid)?;
+        self.excluded_paths.clear();
         
         let file_map = if let Some(old_stats) = &self.old_stats {
             old_stats.clone()
@@ -293,8 +1132,7 @@ impl Repository {
                 file_map.clear();
                 
                 // Full scan
-                let tree = self.get_tree(self.commit_oid)?;
-                self.process_tree(&tree, "", &file_map)?;
+                self.scan_tree(&file_map)?;
             } else {
                 // Process only changed files
                 for delta in diff.deltas() {
@@ -323,6 +1161,12 @@ impl Repository {
                     };
                     
                     if is_binary {
+                        self.excluded_paths.insert(new_path, ExclusionReason::Binary);
+                        continue;
+                    }
+                    
+                    if Self::exceeds_max_file_size(&self.repo, delta.new_file().id(), self.max_file_size)? {
+                        self.excluded_paths.insert(new_path, ExclusionReason::TooBig);
                         continue;
                     }
                     
@@ -330,7 +1174,11 @@ impl Repository {
                     if delta.status() == git2::Delta::Added || delta.status() == git2::Delta::Modified {
                         // Skip submodules and symlinks
                         let mode = delta.new_file().mode();
-                        if mode == FileMode::Link || mode == FileMode::Commit {
+                        if mode == FileMode::Commit {
+                            continue;
+                        }
+                        if mode == FileMode::Link {
+                            self.excluded_paths.insert(new_path, ExclusionReason::Symlink);
                             continue;
                         }
                         
@@ -345,23 +1193,43 @@ impl Repository {
                         );
                         
                         // Update file map if included in language stats
-                        if blob.include_in_language_stats() {
-                            if let Some(language) = blob.language() {
-                                file_map.insert(new_path, (language.group().unwrap().name.clone(), blob.size()));
-                            }
+                        match Self::file_stats(&self.gitattributes, &blob, &new_path) {
+                            Ok(stats) => { file_map.insert(new_path, stats); },
+                            Err(Some(reason)) => { self.excluded_paths.insert(new_path, reason); },
+                            Err(None) => (),
                         }
                     }
                 }
             }
         } else {
             // Full scan if no previous stats
-            let tree = self.get_tree(self.commit_oid)?;
-            self.process_tree(&tree, "", &file_map)?;
+            self.scan_tree(&file_map)?;
         }
         
         Ok(file_map)
     }
     
+    /// Analyze every file in the commit's tree
+    ///
+    /// Uses `process_tree_parallel` when rayon has more than one thread to
+    /// work with, and the sequential `process_tree` otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_map` - Map to store results
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error
+    fn scan_tree(&self, file_map: &FileStatsCache) -> Result<()> {
+        let tree = self.get_tree(self.commit_oid)?;
+        
+        if rayon::current_num_threads() < 2 {
+            return self.process_tree(&tree, "", file_map);
+        }
+        
+        self.process_tree_parallel(&tree, "", file_map)
+    }
     
     /// Process a tree recursively
     ///
@@ -391,7 +1259,16 @@ impl Repository {
                 Some(ObjectType::Blob) => {
                     // Skip submodules and symlinks
                     let mode = entry.filemode();
-                    if mode == FileMode::Link as i32 || mode == FileMode::Commit as i32 {
+                    if mode == FileMode::Commit as i32 {
+                        continue;
+                    }
+                    if mode == FileMode::Link as i32 {
+                        self.excluded_paths.insert(path, ExclusionReason::Symlink);
+                        continue;
+                    }
+                    
+                    if Self::exceeds_max_file_size(&self.repo, entry.id(), self.max_file_size)? {
+                        self.excluded_paths.insert(path, ExclusionReason::TooBig);
                         continue;
                     }
                     
@@ -405,12 +1282,13 @@ impl Repository {
                     );
                     
                     // Update file map if included in language stats
-                    if blob.include_in_language_stats() {
-                        if let Some(language) = blob.language() {
-                            file_map.insert(path, (language.group().unwrap().name.clone(), blob.size()));
-                        }
+                    match Self::file_stats(&self.gitattributes, &blob, &path) {
+                        Ok(stats) => { file_map.insert(path, stats); },
+                        Err(Some(reason)) => { self.excluded_paths.insert(path, reason); },
+                        Err(None) => (),
                     }
                 },
+                Some(ObjectType::Commit) => log::debug!("Skipping submodule {}", path),
                 _ => (), // Skip other types
             }
         }
@@ -418,6 +1296,155 @@ impl Repository {
         Ok(())
     }
     
+    /// Process a tree, analyzing its blobs in parallel
+    ///
+    /// The blobs of the whole tree are collected first, then analyzed on the
+    /// rayon thread pool in a single pass, so each worker opens its handle on
+    /// the repository once rather than once per directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The Git tree
+    /// * `prefix` - Path prefix for entries
+    /// * `file_map` - Map to store results
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error
+    fn process_tree_parallel(&self, tree: &Tree, prefix: &str, file_map: &FileStatsCache) -> Result<()> {
+        let mut blobs = Vec::new();
+        self.collect_blobs(tree, prefix, &mut blobs)?;
+        
+        // git2 repositories can't be shared between threads, so every worker opens its own
+        let repo_path = self.repo.path().to_path_buf();
+        let gitattributes = &self.gitattributes;
+        let excluded_paths = &self.excluded_paths;
+        let max_file_size = self.max_file_size;
+        blobs.into_par_iter().try_for_each_init(
+            || GitRepository::open(&repo_path).map(Arc::new),
+            |repo, (path, oid, mode)| -> Result<()> {
+                let repo = match repo {
+                    Ok(repo) => repo.clone(),
+                    Err(err) => return Err(git2::Error::new(err.code(), err.class(), err.message()).into()),
+                };
+                
+                if Self::exceeds_max_file_size(&repo, oid, max_file_size)? {
+                    excluded_paths.insert(path, ExclusionReason::TooBig);
+                    return Ok(());
+                }
+                
+                let blob = LazyBlob::new(repo, oid, path.clone(), Some(format!("{:o}", mode as u32)));
+                match Self::file_stats(gitattributes, &blob, &path) {
+                    Ok(stats) => { file_map.insert(path, stats); },
+                    Err(Some(reason)) => { excluded_paths.insert(path, reason); },
+                    Err(None) => (),
+                }
+                
+                Ok(())
+            },
+        )
+    }
+    
+    /// Collect the blobs of a tree and its subtrees
+    ///
+    /// Symlinks are recorded as excluded, and submodules are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The Git tree
+    /// * `prefix` - Path prefix for entries
+    /// * `blobs` - The path, object ID and file mode of each blob found
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error
+    fn collect_blobs(&self, tree: &Tree, prefix: &str, blobs: &mut Vec<(String, Oid, i32)>) -> Result<()> {
+        for entry in tree.iter() {
+            let name = entry.name().unwrap_or_default();
+            let path = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+            
+            match entry.kind() {
+                Some(ObjectType::Tree) => {
+                    let subtree = self.repo.find_tree(entry.id())?;
+                    self.collect_blobs(&subtree, &path, blobs)?;
+                },
+                Some(ObjectType::Blob) => {
+                    // Skip submodules and symlinks
+                    let mode = entry.filemode();
+                    if mode == FileMode::Link as i32 {
+                        self.excluded_paths.insert(path, ExclusionReason::Symlink);
+                    } else if mode != FileMode::Commit as i32 {
+                        blobs.push((path, entry.id(), mode));
+                    }
+                },
+                Some(ObjectType::Commit) => log::debug!("Skipping submodule {}", path),
+                _ => (), // Skip other types
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check whether a blob is larger than `max_file_size`, without loading its content
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The Git repository to read the object header from
+    /// * `oid` - The ID of the blob object
+    /// * `max_file_size` - The maximum file size, None for unlimited
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - True if the blob should be skipped
+    fn exceeds_max_file_size(repo: &GitRepository, oid: Oid, max_file_size: Option<usize>) -> Result<bool> {
+        let max = match max_file_size {
+            Some(max) => max,
+            None => return Ok(false),
+        };
+        
+        let (size, _) = repo.odb()?.read_header(oid)?;
+        Ok(size > max)
+    }
+    
+    /// Compute the language and size of a blob, honoring `.gitattributes` overrides
+    ///
+    /// # Arguments
+    ///
+    /// * `gitattributes` - Linguist overrides with their compiled path patterns
+    /// * `blob` - The blob to analyze
+    /// * `path` - The repository-relative path of the blob
+    ///
+    /// # Returns
+    ///
+    /// * `std::result::Result<(String, usize), Option<ExclusionReason>>` - The language group
+    ///   name and size, or the reason the blob is excluded. Blobs of a language that isn't
+    ///   detectable, such as data or prose, are left out without a reason.
+    fn file_stats<B: BlobHelper + ?Sized>(
+        gitattributes: &[(regex::Regex, GitAttributeOverride)],
+        blob: &B,
+        path: &str,
+    ) -> std::result::Result<(String, usize), Option<ExclusionReason>> {
+        if blob.is_symlink() {
+            return Err(Some(ExclusionReason::Symlink));
+        }
+        
+        let attributes = GitAttributeOverride::for_path(gitattributes, path);
+        
+        match include_stats(blob, &attributes) {
+            (InclusionReason::Included, Some(language)) => Ok((language.resolve_group().name.clone(), blob.size())),
+            (InclusionReason::Binary, _) => Err(Some(ExclusionReason::Binary)),
+            (InclusionReason::Vendored { .. }, _) => Err(Some(ExclusionReason::Vendored)),
+            (InclusionReason::Generated { .. }, _) => Err(Some(ExclusionReason::Generated)),
+            (InclusionReason::Documentation, _) => Err(Some(ExclusionReason::Documentation)),
+            (InclusionReason::NoLanguageDetected, _) | (InclusionReason::Included, None) => Err(Some(ExclusionReason::Unknown)),
+            (InclusionReason::UnsupportedLanguageType, _) => Err(None),
+        }
+    }
+    
     /// Get the tree for a commit
     ///
     /// # Arguments
@@ -482,6 +1509,9 @@ impl Repository {
     
     /// Set up attribute source for GitAttributes
     ///
+    /// Reads the root `.gitattributes` file of the commit's tree and stores
+    /// its linguist overrides, compiling each path pattern once.
+    ///
     /// # Arguments
     ///
     /// * `oid` - The commit ID
@@ -489,10 +1519,22 @@ impl Repository {
     /// # Returns
     ///
     /// * `Result<()>` - Success or error
-    fn set_attribute_source(&self, _oid: Oid) -> Result<()> {
-        // This is a simplified placeholder
-        // In a real implementation, we would set up a real attribute source
-        // based on .gitattributes files in the repository
+    fn set_attribute_source(&mut self, oid: Oid) -> Result<()> {
+        let content = {
+            let tree = self.get_tree(oid)?;
+            
+            match tree.get_path(Path::new(".gitattributes")) {
+                Ok(entry) => {
+                    let blob = self.repo.find_blob(entry.id())?;
+                    Some(String::from_utf8_lossy(blob.content()).into_owned())
+                },
+                Err(_) => None, // No .gitattributes in this commit
+            }
+        };
+        
+        self.gitattributes = content
+            .map(|content| GitAttributeOverride::compile(&content))
+            .unwrap_or_default();
         
         Ok(())
     }
@@ -503,6 +1545,12 @@ pub struct DirectoryAnalyzer {
     /// Root directory path
     root: PathBuf,
     
+    /// Maximum directory depth to descend into, None for unlimited
+    max_depth: Option<usize>,
+    
+    /// Whether to descend into symlinked directories
+    follow_symlinks: bool,
+    
     /// Analysis cache
     cache: Option<FileStatsCache>,
 }
@@ -520,20 +1568,70 @@ impl DirectoryAnalyzer {
     pub fn new<P: AsRef<Path>>(root: P) -> Self {
         Self {
             root: root.as_ref().to_path_buf(),
+            max_depth: None,
+            follow_symlinks: false,
             cache: None,
         }
     }
     
+    /// Limit how deep the analyzer descends into subdirectories
+    ///
+    /// A depth of 0 only analyzes files directly inside the root directory,
+    /// 1 also analyzes files one directory deep, and so on.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The deepest directory level to analyze
+    ///
+    /// # Returns
+    ///
+    /// * `DirectoryAnalyzer` - The updated analyzer
+    pub fn with_depth_limit(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+    
+    /// Choose whether symbolic links are followed while walking the directory
+    ///
+    /// Links are not followed by default, since symlinked directories can
+    /// create cycles or lead outside the analyzed tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `follow` - Whether to follow symbolic links
+    ///
+    /// # Returns
+    ///
+    /// * `DirectoryAnalyzer` - The updated analyzer
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+    
     /// Analyze the directory
     ///
     /// # Returns
     ///
     /// * `Result<LanguageStats>` - The language statistics
     pub fn analyze(&mut self) -> Result<LanguageStats> {
+        self.analyze_with_progress(|_, _| {})
+    }
+    
+    /// Analyze the directory, reporting progress as files are processed
+    ///
+    /// # Arguments
+    ///
+    /// * `on_progress` - Called with `(processed, total)` after each file is processed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LanguageStats>` - The language statistics
+    pub fn analyze_with_progress(&mut self, on_progress: impl Fn(usize, usize) + Send + Sync) -> Result<LanguageStats> {
         let file_map = DashMap::new();
+        let excluded_paths = DashMap::new();
         
         // Traverse the directory with parallel processing
-        self.process_directory(&self.root, &file_map)?;
+        self.process_directory(&self.root, &file_map, &excluded_paths, &on_progress)?;
         
         self.cache = Some(file_map);
         
@@ -541,64 +1639,114 @@ impl DirectoryAnalyzer {
         let total_size = self.size()?;
         let language = self.language()?;
         let file_breakdown = self.breakdown_by_file()?;
+        let file_sizes = self.get_cache()?.iter()
+            .map(|entry| (entry.key().clone(), entry.value().1))
+            .collect();
+        let mut excluded_files: Vec<_> = excluded_paths.into_iter().collect();
+        excluded_files.sort();
         
         Ok(LanguageStats {
             language_breakdown,
             total_size,
             language,
             file_breakdown,
+            file_sizes,
+            submodule_paths: Vec::new(),
+            skipped_files: 0,
+            excluded_files,
         })
     }
     
+    /// Clear the analysis cache
+    ///
+    /// The next call to `analyze` walks the directory again.
+    pub fn reset(&mut self) {
+        self.cache = None;
+    }
+    
     /// Process a directory recursively with parallel processing
     ///
     /// # Arguments
     ///
     /// * `dir` - Directory to process
     /// * `file_map` - Map to store results
+    /// * `excluded_paths` - Map to store excluded files and the reason
+    /// * `on_progress` - Called with `(processed, total)` after each file is processed
     ///
     /// # Returns
     ///
     /// * `Result<()>` - Success or error
-    fn process_directory(&self, dir: &Path, file_map: &FileStatsCache) -> Result<()> {
+    fn process_directory(
+        &self,
+        dir: &Path,
+        file_map: &FileStatsCache,
+        excluded_paths: &DashMap<String, ExclusionReason>,
+        on_progress: &(impl Fn(usize, usize) + Send + Sync),
+    ) -> Result<()> {
         // Collect all file entries first
-        let entries: Vec<_> = walkdir::WalkDir::new(dir)
-            .follow_links(false)
+        let mut walker = walkdir::WalkDir::new(dir)
+            .follow_links(self.follow_symlinks);
+        if self.follow_symlinks {
+            walker = walker.follow_root_links(true);
+        }
+        if let Some(max_depth) = self.max_depth {
+            // walkdir counts the root itself as depth 0
+            walker = walker.max_depth(max_depth + 1);
+        }
+        
+        let entries: Vec<_> = walker
             .into_iter()
             .filter_map(|entry_result| entry_result.ok())
             .filter(|entry| !entry.file_type().is_dir())
             .collect();
         
+        let total = entries.len();
+        let processed = AtomicUsize::new(0);
+        
         // Use Rayon for efficient parallel processing
         entries.par_iter().for_each(|entry| {
-            // Get relative path
-            let path = entry.path().strip_prefix(&self.root)
-                .unwrap_or(entry.path())
-                .to_string_lossy()
-                .to_string();
-                
-            // Skip if path is empty
-            if path.is_empty() {
-                return;
-            }
-                
-            // Create blob and process
-            if let Ok(blob) = FileBlob::new(entry.path()) {
-                // Update file map if included in language stats
-                if blob.include_in_language_stats() {
-                    if let Some(language) = blob.language() {
-                        let group_name = language.group()
-                            .map(|g| g.name.clone())
-                            .unwrap_or(language.name.clone());
-                        file_map.insert(path, (group_name, blob.size()));
-                    }
-                }
-            }
+            self.process_entry(entry, file_map, excluded_paths);
+            on_progress(processed.fetch_add(1, Ordering::Relaxed) + 1, total);
         });
         
         Ok(())
     }
     
+    /// Detect the language of a single directory entry and record it
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to process
+    /// * `file_map` - Map to store results
+    /// * `excluded_paths` - Map to store excluded files and the reason
+    fn process_entry(
+        &self,
+        entry: &walkdir::DirEntry,
+        file_map: &FileStatsCache,
+        excluded_paths: &DashMap<String, ExclusionReason>,
+    ) {
+        // Get relative path
+        let path = entry.path().strip_prefix(&self.root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .to_string();
+            
+        // Skip if path is empty
+        if path.is_empty() {
+            return;
+        }
+            
+        // Create blob and process
+        if let Ok(blob) = FileBlob::with_limit(entry.path(), MAX_READ_BYTES) {
+            // Update file map if included in language stats
+            match Repository::file_stats(&[], &blob, &path) {
+                Ok(stats) => { file_map.insert(path, stats); },
+                Err(Some(reason)) => { excluded_paths.insert(path, reason); },
+                Err(None) => (),
+            }
+        }
+    }
+    
     
     /// Get the breakdown of languages
     ///
@@ -688,7 +1836,31 @@ impl DirectoryAnalyzer {
 mod tests {
     use super::*;
     use std::fs;
-    use tempfile::tempdir;
+    use tempfile::{tempdir, TempDir};
+    
+    /// Create a git repository containing the given files in a single commit
+    fn init_repo(files: &[(&str, &str)]) -> Result<(TempDir, String)> {
+        let dir = tempdir()?;
+        let repo = GitRepository::init(dir.path())?;
+        
+        let mut index = repo.index()?;
+        for (path, content) in files {
+            let full_path = dir.path().join(path);
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&full_path, content)?;
+            index.add_path(Path::new(path))?;
+        }
+        index.write()?;
+        
+        let tree_oid = index.write_tree()?;
+        let tree = repo.find_tree(tree_oid)?;
+        let signature = git2::Signature::now("Linguist", "linguist@example.com")?;
+        let commit_oid = repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])?;
+        
+        Ok((dir, commit_oid.to_string()))
+    }
     
     #[test]
     fn test_directory_analyzer() -> Result<()> {
@@ -738,4 +1910,568 @@ mod tests {
         
         Ok(())
     }
+    
+    #[test]
+    fn test_parse_gitattributes() {
+        let content = "# Linguist overrides\n\
+            *.rb linguist-language=Python\n\
+            vendor/** linguist-vendored\n\
+            docs/* -linguist-documentation text eol=lf\n\
+            *.txt text\n";
+        
+        let attributes = GitAttributeOverride::parse(content);
+        let patterns: Vec<&str> = attributes.iter().map(|(pattern, _)| pattern.as_str()).collect();
+        assert_eq!(patterns, ["*.rb", "vendor/**", "docs/*"]);
+        assert_eq!(attributes[0].1.language.as_deref(), Some("Python"));
+        assert_eq!(attributes[1].1.vendored, Some(true));
+        assert_eq!(attributes[2].1.documentation, Some(false));
+        
+        assert!(GitAttributeOverride::pattern_matches("*.rb", "lib/deep/script.rb"));
+        assert!(GitAttributeOverride::pattern_matches("vendor/**", "vendor/a/b.js"));
+        assert!(!GitAttributeOverride::pattern_matches("/docs/*", "src/docs/a.md"));
+    }
+    
+    #[test]
+    fn test_repository_gitattributes() -> Result<()> {
+        let (dir, commit) = init_repo(&[
+            (".gitattributes", "*.rb linguist-language=Python\nlib/** linguist-vendored\n"),
+            ("script.rb", "puts 'hello'\n"),
+            ("lib/helper.js", "console.log('hello');\n"),
+            ("main.js", "console.log('main');\n"),
+        ])?;
+        
+        let mut repo = Repository::new(dir.path(), &commit, None)?;
+        let breakdown = repo.breakdown_by_file()?;
+        
+        assert_eq!(breakdown["Python"], vec!["script.rb".to_string()]);
+        assert_eq!(breakdown["JavaScript"], vec!["main.js".to_string()]);
+        assert!(!breakdown.contains_key("Ruby"));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_gitattributes_last_match_wins() -> Result<()> {
+        let (dir, commit) = init_repo(&[
+            (".gitattributes", "lib/** linguist-vendored\n*.js -linguist-vendored\nsrc/*.rb linguist-language=Perl\n*.rb linguist-language=Python\n"),
+            ("lib/helper.js", "console.log('hello');\n"),
+            ("src/script.rb", "puts 'hello'\n"),
+        ])?;
+        
+        let mut repo = Repository::new(dir.path(), &commit, None)?;
+        let breakdown = repo.breakdown_by_file()?;
+        
+        // Later lines win over earlier ones, even when their pattern is less specific
+        assert_eq!(breakdown["JavaScript"], vec!["lib/helper.js".to_string()]);
+        assert_eq!(breakdown["Python"], vec!["src/script.rb".to_string()]);
+        assert!(!breakdown.contains_key("Perl"));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_analyze_subtree() -> Result<()> {
+        let (dir, commit) = init_repo(&[
+            ("services/auth/main.rs", "fn main() {}\n"),
+            ("services/auth/helper.py", "print('auth')\n"),
+            ("services/authz/main.js", "console.log('authz');\n"),
+            ("main.js", "console.log('main');\n"),
+        ])?;
+        
+        let mut repo = Repository::new(dir.path(), &commit, None)?;
+        let stats = repo.analyze_subtree("/services/auth/")?;
+        
+        assert_eq!(stats.file_breakdown.len(), 2);
+        assert_eq!(stats.file_breakdown["Rust"], vec!["services/auth/main.rs".to_string()]);
+        assert_eq!(stats.file_breakdown["Python"], vec!["services/auth/helper.py".to_string()]);
+        assert_eq!(stats.total_size, "fn main() {}\n".len() + "print('auth')\n".len());
+        
+        // The prefix is treated as a directory
+        let stats = repo.analyze_subtree("services/auth")?;
+        assert!(!stats.file_breakdown.contains_key("JavaScript"));
+        
+        // An empty prefix covers the whole repository
+        let stats = repo.analyze_subtree("")?;
+        assert_eq!(stats.file_breakdown["JavaScript"].len(), 2);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_file_language() -> Result<()> {
+        let (dir, commit) = init_repo(&[
+            ("src/main.rs", "fn main() {}\n"),
+            ("dist/lib.js", "console.log('bundled');\n"),
+        ])?;
+        
+        let mut repo = Repository::new(dir.path(), &commit, None)?;
+        assert_eq!(repo.file_language("src/main.rs")?, Some("Rust".to_string()));
+        assert_eq!(repo.file_language("/src/main.rs")?, Some("Rust".to_string()));
+        
+        // Excluded and missing files have no language
+        assert_eq!(repo.file_language("dist/lib.js")?, None);
+        assert_eq!(repo.file_language("missing.rs")?, None);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_list_commits() -> Result<()> {
+        let (dir, first) = init_repo(&[("main.rs", "fn main() {}\n")])?;
+        
+        // Add a second commit on top of the first
+        let second = {
+            let repo = GitRepository::open(dir.path())?;
+            let parent = repo.find_commit(Oid::from_str(&first)?)?;
+            let tree = parent.tree()?;
+            let signature = git2::Signature::now("Linguist", "linguist@example.com")?;
+            repo.commit(Some("HEAD"), &signature, &signature, "Second commit", &tree, &[&parent])?
+        };
+        
+        let commits = Repository::list_commits(dir.path(), 10)?;
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].oid, second.to_string());
+        assert_eq!(commits[0].message, "Second commit");
+        assert_eq!(commits[0].author, "Linguist");
+        assert_eq!(commits[1].oid, first);
+        
+        assert_eq!(Repository::list_commits(dir.path(), 1)?.len(), 1);
+        assert!(Repository::list_commits(dir.path(), 0)?.is_empty());
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_resolve_ref() -> Result<()> {
+        let (dir, commit) = init_repo(&[("main.rs", "fn main() {}\n")])?;
+        
+        assert_eq!(Repository::head(dir.path())?, commit);
+        assert_eq!(Repository::resolve_ref(dir.path(), &commit[..7])?, commit);
+        assert!(Repository::resolve_ref(dir.path(), "no-such-branch").is_err());
+        
+        // HEAD can be passed straight to `Repository::new`
+        let mut repo = Repository::new(dir.path(), &Repository::head(dir.path())?, None)?;
+        assert_eq!(repo.language()?, Some("Rust".to_string()));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_empty_repository() -> Result<()> {
+        let dir = tempdir()?;
+        GitRepository::init(dir.path())?;
+        assert!(Repository::is_empty(dir.path())?);
+        
+        let mut repo = Repository::new(dir.path(), &Oid::zero().to_string(), None)?;
+        assert!(repo.languages()?.is_empty());
+        assert_eq!(repo.size()?, 0);
+        
+        let (dir, _) = init_repo(&[("main.rs", "fn main() {}\n")])?;
+        assert!(!Repository::is_empty(dir.path())?);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_process_tree_parallel() -> Result<()> {
+        let (dir, commit) = init_repo(&[
+            ("main.rs", "fn main() {}\n"),
+            ("lib/util.py", "print('util')\n"),
+            ("lib/deep/nested/app.rb", "puts 'app'\n"),
+            ("data/config.json", "{}\n"),
+        ])?;
+        let repo = Repository::new(dir.path(), &commit, None)?;
+        let tree = repo.get_tree(repo.commit_oid)?;
+        
+        let sequential = FileStatsCache::new();
+        repo.process_tree(&tree, "", &sequential)?;
+        
+        let parallel = FileStatsCache::new();
+        repo.process_tree_parallel(&tree, "", &parallel)?;
+        
+        let mut sequential: Vec<_> = sequential.into_iter().collect();
+        let mut parallel: Vec<_> = parallel.into_iter().collect();
+        sequential.sort();
+        parallel.sort();
+        
+        assert_eq!(parallel.len(), 3);
+        assert_eq!(parallel, sequential);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_max_file_size() -> Result<()> {
+        let large = "fn large() {}\n".repeat(100);
+        let (dir, commit) = init_repo(&[
+            ("main.rs", "fn main() {}\n"),
+            ("src/large.rs", large.as_str()),
+            ("lib/util.py", "print('util')\n"),
+        ])?;
+        
+        let mut repo = Repository::new(dir.path(), &commit, None)?.with_max_file_size(100);
+        let stats = repo.stats()?;
+        assert_eq!(stats.skipped_files, 1);
+        assert_eq!(stats.file_sizes.len(), 2);
+        assert!(!stats.file_sizes.contains_key("src/large.rs"));
+        
+        assert_eq!(repo.analyze_subtree("src")?.skipped_files, 1);
+        assert_eq!(repo.analyze_subtree("lib")?.skipped_files, 0);
+        
+        let stats = Repository::new(dir.path(), &commit, None)?.stats()?;
+        assert_eq!(stats.skipped_files, 0);
+        assert_eq!(stats.file_sizes.len(), 3);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_excluded_files() -> Result<()> {
+        let large = "fn large() {}\n".repeat(100);
+        let (dir, commit) = init_repo(&[
+            ("main.rs", "fn main() {}\n"),
+            ("src/large.rs", large.as_str()),
+            ("dist/app.js", "console.log('app');\n"),
+            ("image.bin", "\0\u{1}\u{2}\u{3}"),
+            ("data.unknownext", "just some words\n"),
+        ])?;
+        
+        let mut repo = Repository::new(dir.path(), &commit, None)?.with_max_file_size(100);
+        let stats = repo.stats()?;
+        assert_eq!(stats.excluded_files, vec![
+            ("data.unknownext".to_string(), ExclusionReason::Unknown),
+            ("dist/app.js".to_string(), ExclusionReason::Vendored),
+            ("image.bin".to_string(), ExclusionReason::Binary),
+            ("src/large.rs".to_string(), ExclusionReason::TooBig),
+        ]);
+        assert_eq!(stats.excluded_reason("dist/app.js"), Some(ExclusionReason::Vendored));
+        assert_eq!(stats.excluded_reason("main.rs"), None);
+        assert_eq!(repo.analyze_subtree("src")?.excluded_files.len(), 1);
+        assert_eq!(ExclusionReason::TooBig.description(), "too big");
+        
+        Ok(())
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_excluded_symlinks() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.rs"), "fn main() {}")?;
+        std::os::unix::fs::symlink(dir.path().join("main.rs"), dir.path().join("link.rs"))?;
+        
+        let stats = DirectoryAnalyzer::new(dir.path()).analyze()?;
+        assert_eq!(stats.excluded_files, vec![("link.rs".to_string(), ExclusionReason::Symlink)]);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_directory_analyzer_depth_limit() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("src/nested"))?;
+        fs::write(dir.path().join("main.rs"), "fn main() {}")?;
+        fs::write(dir.path().join("src/lib.py"), "print('lib')")?;
+        fs::write(dir.path().join("src/nested/app.rb"), "puts 'app'")?;
+        
+        let depth_count = |depth: usize| -> Result<usize> {
+            let stats = DirectoryAnalyzer::new(dir.path()).with_depth_limit(depth).analyze()?;
+            Ok(stats.file_sizes.len())
+        };
+        
+        assert_eq!(depth_count(0)?, 1);
+        assert_eq!(depth_count(1)?, 2);
+        assert_eq!(depth_count(2)?, 3);
+        assert_eq!(DirectoryAnalyzer::new(dir.path()).analyze()?.file_sizes.len(), 3);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_directory_analyzer_read_limit() -> Result<()> {
+        let dir = tempdir()?;
+        let content = "package main\n\nfunc main() {}\n".repeat(MAX_READ_BYTES / 10);
+        assert!(content.len() > MAX_READ_BYTES);
+        fs::write(dir.path().join("main.go"), &content)?;
+        
+        let stats = DirectoryAnalyzer::new(dir.path()).analyze()?;
+        assert_eq!(stats.language_breakdown.get("Go"), Some(&content.len()));
+        
+        Ok(())
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_directory_analyzer_follow_symlinks() -> Result<()> {
+        let target = tempdir()?;
+        fs::write(target.path().join("lib.py"), "print('lib')")?;
+        
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.rs"), "fn main() {}")?;
+        std::os::unix::fs::symlink(target.path(), dir.path().join("linked"))?;
+        
+        let stats = DirectoryAnalyzer::new(dir.path()).analyze()?;
+        assert!(!stats.language_breakdown.contains_key("Python"));
+        
+        let stats = DirectoryAnalyzer::new(dir.path()).follow_symlinks(true).analyze()?;
+        assert!(stats.file_sizes.contains_key(&format!("linked{}lib.py", std::path::MAIN_SEPARATOR)));
+        assert_eq!(stats.language_breakdown.get("Python"), Some(&"print('lib')".len()));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_submodule_paths() -> Result<()> {
+        let (dir, _) = init_repo(&[("main.rs", "fn main() {}\n")])?;
+        let repo = GitRepository::open(dir.path())?;
+        let head = repo.head()?.peel_to_commit()?;
+        
+        // Add gitlinks at `lib` and `deps/other`, as `git submodule add` would
+        let submodule_oid = head.id();
+        let mut deps = repo.treebuilder(None)?;
+        deps.insert("other", submodule_oid, FileMode::Commit.into())?;
+        let deps_oid = deps.write()?;
+        
+        let mut root = repo.treebuilder(Some(&head.tree()?))?;
+        root.insert("lib", submodule_oid, FileMode::Commit.into())?;
+        root.insert("deps", deps_oid, FileMode::Tree.into())?;
+        let tree = repo.find_tree(root.write()?)?;
+        
+        let signature = git2::Signature::now("Linguist", "linguist@example.com")?;
+        let commit = repo.commit(Some("HEAD"), &signature, &signature, "Add submodules", &tree, &[&head])?;
+        
+        let mut repository = Repository::new(dir.path(), &commit.to_string(), None)?;
+        assert_eq!(repository.submodule_paths()?, vec!["deps/other".to_string(), "lib".to_string()]);
+        
+        let stats = repository.stats()?;
+        assert_eq!(stats.submodule_paths, vec!["deps/other".to_string(), "lib".to_string()]);
+        assert_eq!(stats.file_sizes.len(), 1);
+        
+        assert_eq!(repository.analyze_subtree("deps")?.submodule_paths, vec!["deps/other".to_string()]);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_walk_tree_entries() -> Result<()> {
+        let (dir, commit) = init_repo(&[
+            ("main.rs", "fn main() {}\n"),
+            ("lib/util.py", "print('util')\n"),
+        ])?;
+        let repo = Repository::new(dir.path(), &commit, None)?;
+        
+        let entries = repo.walk_tree_entries()?;
+        let paths: Vec<(&str, ObjectType)> = entries.iter()
+            .map(|entry| (entry.path.as_str(), entry.kind))
+            .collect();
+        assert_eq!(paths, vec![
+            ("lib", ObjectType::Tree),
+            ("lib/util.py", ObjectType::Blob),
+            ("main.rs", ObjectType::Blob),
+        ]);
+        
+        let main = entries.iter().find(|entry| entry.path == "main.rs").unwrap();
+        assert_eq!(main.mode, i32::from(FileMode::Blob));
+        assert_eq!(repo.repo.find_blob(main.oid)?.content(), b"fn main() {}\n");
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_directory_analyzer_reset() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.rs"), "fn main() {}")?;
+        
+        let mut analyzer = DirectoryAnalyzer::new(dir.path());
+        let stats = analyzer.analyze()?;
+        assert!(!stats.file_breakdown.contains_key("Python"));
+        
+        analyzer.reset();
+        assert!(analyzer.get_cache().is_err());
+        
+        // Files added after the reset show up in the next analysis
+        fs::write(dir.path().join("hello.py"), "print('Hello, world!')")?;
+        let stats = analyzer.analyze()?;
+        assert!(stats.file_breakdown.contains_key("Rust"));
+        assert!(stats.file_breakdown.contains_key("Python"));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_directory_analyzer_progress() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.rs"), "fn main() {}")?;
+        fs::write(dir.path().join("hello.py"), "print('Hello, world!')")?;
+        fs::write(dir.path().join("notes.txt"), "notes")?;
+        
+        let calls = AtomicUsize::new(0);
+        let mut analyzer = DirectoryAnalyzer::new(dir.path());
+        let stats = analyzer.analyze_with_progress(|processed, total| {
+            assert_eq!(total, 3);
+            assert!(processed >= 1 && processed <= total);
+            calls.fetch_add(1, Ordering::Relaxed);
+        })?;
+        
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+        assert!(stats.file_breakdown.contains_key("Rust"));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_language_stats_diff() -> Result<()> {
+        let (dir, old_commit) = init_repo(&[
+            ("main.rs", "fn main() {}"),
+            ("script.py", "print('Hello, world!')"),
+            ("lib.rb", "puts 'hello'"),
+        ])?;
+        
+        // Grow Rust, shrink Python, drop Ruby and add Go
+        let repo = GitRepository::open(dir.path())?;
+        let mut index = repo.index()?;
+        fs::write(dir.path().join("main.rs"), "fn main() { println!(\"Hello, world!\"); }")?;
+        fs::write(dir.path().join("script.py"), "print(1)")?;
+        fs::write(dir.path().join("main.go"), "package main\n\nfunc main() {}\n")?;
+        index.add_path(Path::new("main.rs"))?;
+        index.add_path(Path::new("script.py"))?;
+        index.add_path(Path::new("main.go"))?;
+        index.remove_path(Path::new("lib.rb"))?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        let parent = repo.head()?.peel_to_commit()?;
+        let new_commit = repo.commit(Some("HEAD"), &signature, &signature, "Update", &tree, &[&parent])?.to_string();
+        
+        let mut old_repo = Repository::new(dir.path(), &old_commit, None)?;
+        let old_stats = old_repo.stats()?;
+        let mut new_repo = Repository::incremental(
+            dir.path(), &new_commit, &old_commit, old_repo.file_stats_cache()?, None
+        )?;
+        let new_stats = new_repo.stats()?;
+        
+        let diff = old_stats.diff(&new_stats);
+        let names = |changes: &[LanguageChange]| changes.iter().map(|c| c.language.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&diff.grown), vec!["Rust"]);
+        assert_eq!(names(&diff.shrunk), vec!["Python"]);
+        assert_eq!(names(&diff.added), vec!["Go"]);
+        assert_eq!(names(&diff.removed), vec!["Ruby"]);
+        assert_eq!(diff.removed[0].new_size, 0);
+        
+        assert!(new_stats.diff(&new_stats).is_empty());
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_file_size_histogram() {
+        let file_sizes: HashMap<String, usize> = [("a", 0), ("b", 1), ("c", 3), ("d", 4), ("e", 100)]
+            .iter()
+            .map(|(name, size)| (name.to_string(), *size))
+            .collect();
+        let stats = LanguageStats {
+            language_breakdown: HashMap::new(),
+            total_size: 108,
+            language: None,
+            file_breakdown: HashMap::new(),
+            file_sizes,
+            submodule_paths: Vec::new(),
+            skipped_files: 0,
+            excluded_files: Vec::new(),
+        };
+        
+        let histogram = stats.file_size_histogram(16);
+        assert_eq!(histogram, vec![(1, 2), (2, 0), (4, 2), (8, 0), (16, 0), (32, 0), (64, 0), (128, 1)]);
+        
+        // Larger files are folded into the last bucket
+        let histogram = stats.file_size_histogram(3);
+        assert_eq!(histogram, vec![(1, 2), (2, 0), (128, 3)]);
+        
+        assert!(stats.file_size_histogram(0).is_empty());
+    }
+    
+    #[test]
+    fn test_summary() {
+        let stats = LanguageStats {
+            language_breakdown: [("Rust".to_string(), 75), ("Python".to_string(), 25)].into_iter().collect(),
+            total_size: 100,
+            language: Some("Rust".to_string()),
+            file_breakdown: [
+                ("Rust".to_string(), vec!["a.rs".to_string(), "b.rs".to_string()]),
+                ("Python".to_string(), vec!["c.py".to_string()]),
+            ].into_iter().collect(),
+            file_sizes: HashMap::new(),
+            submodule_paths: Vec::new(),
+            skipped_files: 0,
+            excluded_files: Vec::new(),
+        };
+        
+        let summary = stats.summary();
+        assert_eq!(summary.total_files, 3);
+        assert_eq!(summary.total_bytes, 100);
+        assert_eq!(summary.unique_languages, 2);
+        assert_eq!(summary.top_language.as_deref(), Some("Rust"));
+        assert!((summary.top_language_percentage - 75.0).abs() < 1e-10);
+    }
+    
+    #[test]
+    fn test_to_chart_data() {
+        let stats = LanguageStats {
+            language_breakdown: [
+                ("Python".to_string(), 25),
+                ("Rust".to_string(), 70),
+                ("Internal DSL".to_string(), 5),
+            ].into_iter().collect(),
+            total_size: 100,
+            language: Some("Rust".to_string()),
+            file_breakdown: HashMap::new(),
+            file_sizes: HashMap::new(),
+            submodule_paths: Vec::new(),
+            skipped_files: 0,
+            excluded_files: Vec::new(),
+        };
+        
+        let data = stats.to_chart_data();
+        let entries = data.as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        
+        assert_eq!(entries[0]["label"], "Rust");
+        assert_eq!(entries[0]["color"], "#dea584");
+        assert!((entries[0]["value"].as_f64().unwrap() - 0.7).abs() < 1e-10);
+        assert_eq!(entries[1]["label"], "Python");
+        
+        // Unknown languages get a stable generated color
+        assert_eq!(entries[2]["label"], "Internal DSL");
+        let color = entries[2]["color"].as_str().unwrap();
+        assert_eq!(color.len(), 7);
+        assert!(color.starts_with('#'));
+        assert_eq!(color, fallback_color("Internal DSL"));
+    }
+    
+    #[test]
+    fn test_files_by_language() {
+        let stats = LanguageStats {
+            language_breakdown: HashMap::new(),
+            total_size: 0,
+            language: None,
+            file_breakdown: [
+                ("Rust".to_string(), vec!["a.rs".to_string(), "b.rs".to_string()]),
+                ("Python".to_string(), vec!["c.py".to_string()]),
+            ].into_iter().collect(),
+            file_sizes: HashMap::new(),
+            submodule_paths: Vec::new(),
+            skipped_files: 0,
+            excluded_files: Vec::new(),
+        };
+        
+        let breakdown = stats.files_by_language();
+        assert_eq!(breakdown.language_count(), 2);
+        assert_eq!(breakdown.for_language("Python"), Some(&["c.py".to_string()][..]));
+        assert_eq!(breakdown.for_language("Go"), None);
+        
+        let mut pairs: Vec<(&str, usize)> = breakdown.into_iter()
+            .map(|(language, files)| (language, files.len()))
+            .collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![("Python", 1), ("Rust", 2)]);
+    }
 }
\ No newline at end of file
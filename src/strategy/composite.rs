@@ -0,0 +1,144 @@
+//! Composite detection strategy.
+//!
+//! This strategy runs several strategies on the same blob and combines
+//! their results, for pipelines where no single strategy should decide.
+
+use crate::blob::BlobHelper;
+use crate::language::Language;
+use crate::strategy::{Strategy, StrategyType};
+
+/// Strategy combining the results of several strategies
+///
+/// ```
+/// use linguist::blob::FileBlob;
+/// use linguist::strategy::{Strategy, StrategyType};
+/// use linguist::strategy::composite::Composite;
+/// use linguist::strategy::extension::Extension;
+/// use linguist::strategy::shebang::Shebang;
+///
+/// let composite = StrategyType::Composite(Composite::new(vec![
+///     StrategyType::Extension(Extension),
+///     StrategyType::Shebang(Shebang),
+/// ], false));
+///
+/// let blob = FileBlob::from_data("script.rb", b"#!/usr/bin/env python\n".to_vec());
+/// let names: Vec<_> = composite.call(&blob, &[]).into_iter().map(|lang| lang.name).collect();
+/// assert_eq!(names, ["Ruby", "Python"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Composite {
+    /// The strategies to run, in order
+    strategies: Vec<StrategyType>,
+    
+    /// Whether a language must be returned by every strategy
+    require_consensus: bool,
+}
+
+impl Composite {
+    /// Create a new Composite strategy
+    ///
+    /// # Arguments
+    ///
+    /// * `strategies` - The strategies to run
+    /// * `require_consensus` - If true, only keep languages returned by every
+    ///   strategy; otherwise keep languages returned by any of them
+    ///
+    /// # Returns
+    ///
+    /// * `Composite` - The strategy
+    pub fn new(strategies: Vec<StrategyType>, require_consensus: bool) -> Self {
+        Self {
+            strategies,
+            require_consensus,
+        }
+    }
+    
+    /// Get the strategies this strategy runs
+    ///
+    /// # Returns
+    ///
+    /// * `&[StrategyType]` - The strategies, in order
+    pub fn strategies(&self) -> &[StrategyType] {
+        &self.strategies
+    }
+    
+    /// Check whether languages must be returned by every strategy
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if results are intersected, false if they are unioned
+    pub fn requires_consensus(&self) -> bool {
+        self.require_consensus
+    }
+}
+
+impl Strategy for Composite {
+    fn call<B: BlobHelper + ?Sized>(&self, blob: &B, candidates: &[Language]) -> Vec<Language> {
+        let mut results = self.strategies.iter()
+            .map(|strategy| strategy.call(blob, candidates));
+        
+        let mut languages: Vec<Language> = Vec::new();
+        if self.require_consensus {
+            // Keep the order of the first strategy's results
+            if let Some(first) = results.next() {
+                languages = first;
+                for result in results {
+                    languages.retain(|language| result.contains(language));
+                }
+            }
+        } else {
+            for language in results.flatten() {
+                if !languages.contains(&language) {
+                    languages.push(language);
+                }
+            }
+        }
+        
+        languages
+    }
+    
+    fn is_definitive(&self) -> bool {
+        self.strategies.iter().all(Strategy::is_definitive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blob::FileBlob;
+    use crate::strategy::{extension::Extension, shebang::Shebang, xml::Xml};
+    
+    fn names(strategy: &Composite, name: &str, content: &str) -> Vec<String> {
+        let blob = FileBlob::from_data(name, content.as_bytes().to_vec());
+        strategy.call(&blob, &[]).into_iter().map(|language| language.name).collect()
+    }
+    
+    #[test]
+    fn test_union() {
+        let composite = Composite::new(vec![
+            StrategyType::Extension(Extension),
+            StrategyType::Shebang(Shebang),
+        ], false);
+        
+        assert_eq!(names(&composite, "script.rb", "#!/usr/bin/env python\n"), ["Ruby", "Python"]);
+        assert_eq!(names(&composite, "script.py", "#!/usr/bin/env python\n"), ["Python"]);
+        assert_eq!(names(&composite, "script", "#!/usr/bin/env python\n"), ["Python"]);
+        assert!(composite.is_definitive());
+        assert!(!composite.requires_consensus());
+    }
+    
+    #[test]
+    fn test_consensus() {
+        let composite = Composite::new(vec![
+            StrategyType::Extension(Extension),
+            StrategyType::Shebang(Shebang),
+        ], true);
+        
+        assert!(names(&composite, "script.rb", "#!/usr/bin/env python\n").is_empty());
+        assert!(names(&composite, "script", "#!/usr/bin/env python\n").is_empty());
+        assert_eq!(names(&composite, "script.py", "#!/usr/bin/env python\n"), ["Python"]);
+        
+        assert!(Composite::new(Vec::new(), true).call(&FileBlob::from_data("a.py", Vec::new()), &[]).is_empty());
+        assert!(!Composite::new(vec![StrategyType::Xml(Xml)], true).is_definitive());
+    }
+}
@@ -0,0 +1,109 @@
+//! Content-Type detection strategy.
+//!
+//! This strategy detects languages from the MIME type of an HTTP
+//! `Content-Type` header, e.g. `text/x-ruby; charset=utf-8`.
+
+use crate::blob::BlobHelper;
+use crate::language::Language;
+use crate::strategy::Strategy;
+
+// MIME types servers send for any text or binary file, which say nothing about the language
+const GENERIC_MIME_TYPES: &[&str] = &["text/plain", "application/octet-stream"];
+
+/// Content-Type detection strategy
+///
+/// The default strategy reads the header from the blob (see
+/// `BlobHelper::content_type`); `from_header` uses a fixed header instead.
+#[derive(Debug, Clone, Default)]
+pub struct ContentType {
+    /// MIME type to use for every blob, overriding the blob's own header
+    mime_type: Option<String>,
+}
+
+impl ContentType {
+    /// Create a strategy for a `Content-Type` header
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header value, e.g. `text/x-ruby; charset=utf-8`
+    ///
+    /// # Returns
+    ///
+    /// * `ContentType` - The strategy
+    pub fn from_header(header: &str) -> ContentType {
+        ContentType {
+            mime_type: Some(Self::mime_type(header)),
+        }
+    }
+    
+    /// Get the MIME type of the strategy's header, if it has one
+    pub fn media_type(&self) -> Option<&str> {
+        self.mime_type.as_deref()
+    }
+    
+    /// Strip parameters such as `; charset=utf-8` from a header value
+    fn mime_type(header: &str) -> String {
+        header.split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+    }
+}
+
+impl Strategy for ContentType {
+    fn call<B: BlobHelper + ?Sized>(&self, blob: &B, candidates: &[Language]) -> Vec<Language> {
+        let mime_type = match (&self.mime_type, blob.content_type()) {
+            (Some(mime_type), _) => mime_type.clone(),
+            (None, Some(header)) => Self::mime_type(header),
+            (None, None) => return Vec::new(),
+        };
+        
+        if GENERIC_MIME_TYPES.contains(&mime_type.as_str()) {
+            return Vec::new();
+        }
+        
+        match Language::all_mime_types().get(&mime_type) {
+            Some(language) if candidates.is_empty() || candidates.contains(language) => vec![(*language).clone()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blob::FileBlob;
+    use std::path::Path;
+    
+    #[test]
+    fn test_from_header() {
+        let strategy = ContentType::from_header("text/x-ruby; charset=utf-8");
+        assert_eq!(strategy.media_type(), Some("text/x-ruby"));
+        
+        let blob = FileBlob::from_data(Path::new("script"), b"puts 'hello'".to_vec());
+        let languages = strategy.call(&blob, &[]);
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].name, "Ruby");
+        
+        // Candidates from earlier strategies restrict the result
+        let python = Language::find_by_name("Python").unwrap().clone();
+        assert!(strategy.call(&blob, &[python]).is_empty());
+        
+        // Generic types are ignored
+        assert!(ContentType::from_header("text/plain; charset=utf-8").call(&blob, &[]).is_empty());
+    }
+    
+    #[test]
+    fn test_blob_content_type() {
+        let blob = FileBlob::from_data(Path::new("script"), b"x = 1".to_vec())
+            .with_content_type("text/x-python");
+        
+        let languages = ContentType::default().call(&blob, &[]);
+        assert_eq!(languages[0].name, "Python");
+        assert_eq!(blob.language().unwrap().name, "Python");
+        
+        let blob = FileBlob::from_data(Path::new("script"), b"x = 1".to_vec());
+        assert!(ContentType::default().call(&blob, &[]).is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+//! Manifest-based language detection strategy.
+//!
+//! This strategy detects the language of a project from well-known
+//! build and package manifest files such as `Cargo.toml` or `package.json`.
+//!
+//! Manifests are files of their own format, so the strategy is not part of
+//! the default detection order. `Manifest::languages` gives the project
+//! languages as a repository-level hint, and the strategy can still be
+//! added to a custom pipeline.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::blob::BlobHelper;
+use crate::language::Language;
+use crate::strategy::Strategy;
+
+/// Manifest filenames and the languages they indicate
+const MANIFESTS: &[(&str, &[&str])] = &[
+    ("Cargo.toml", &["Rust"]),
+    ("package.json", &["JavaScript", "TypeScript"]),
+    ("go.mod", &["Go"]),
+    ("Gemfile", &["Ruby"]),
+    ("setup.py", &["Python"]),
+    ("pyproject.toml", &["Python"]),
+    ("pom.xml", &["Java"]),
+    ("build.gradle", &["Java", "Kotlin"]),
+    ("build.gradle.kts", &["Kotlin"]),
+    ("composer.json", &["PHP"]),
+    ("mix.exs", &["Elixir"]),
+    ("Package.swift", &["Swift"]),
+    ("pubspec.yaml", &["Dart"]),
+];
+
+/// Manifest-based language detection strategy
+#[derive(Debug, Clone)]
+pub struct Manifest;
+
+impl Manifest {
+    /// Get the languages indicated by a manifest filename
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - The filename or path to check
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&Language>` - The languages for the manifest, empty if it is not a known manifest
+    pub fn languages(filename: &str) -> Vec<&'static Language> {
+        let basename = Path::new(filename)
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or("");
+        
+        MANIFESTS.iter()
+            .find(|(manifest, _)| *manifest == basename)
+            .map(|(_, names)| names.iter().filter_map(|name| Language::find_by_name(name)).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Strategy for Manifest {
+    fn call<B: BlobHelper + ?Sized>(&self, blob: &B, candidates: &[Language]) -> Vec<Language> {
+        // A manifest written in a data format (`package.json`, `Cargo.toml`)
+        // is still that format; only narrow files with no language of their own
+        if !Language::find_by_extension_all(blob.name()).is_empty() {
+            return candidates.to_vec();
+        }
+        
+        let languages = Self::languages(blob.name());
+        
+        // Filter by candidates if provided
+        if !candidates.is_empty() {
+            let candidate_set: HashSet<_> = candidates.iter().collect();
+            languages.into_iter()
+                .filter(|lang| candidate_set.contains(lang))
+                .cloned()
+                .collect()
+        } else {
+            languages.into_iter().cloned().collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blob::FileBlob;
+    
+    #[test]
+    fn test_manifest_languages() {
+        let names = |filename| Manifest::languages(filename).iter().map(|lang| lang.name.as_str()).collect::<Vec<_>>();
+        
+        assert_eq!(names("project/Cargo.toml"), vec!["Rust"]);
+        assert_eq!(names("package.json"), vec!["JavaScript", "TypeScript"]);
+        assert_eq!(names("Gemfile"), vec!["Ruby"]);
+        assert!(names("Cargo.lock").is_empty());
+        
+        // Every language in the table exists
+        for (manifest, languages) in MANIFESTS {
+            assert_eq!(Manifest::languages(manifest).len(), languages.len(), "{}", manifest);
+        }
+    }
+    
+    #[test]
+    fn test_manifest_strategy() {
+        let strategy = Manifest;
+        
+        let blob = FileBlob::from_data(Path::new("Gemfile"), b"gem \"rails\"".to_vec());
+        let languages = strategy.call(&blob, &[]);
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].name, "Ruby");
+        
+        // Manifests in a data format keep their own language
+        let blob = FileBlob::from_data(Path::new("project/Cargo.toml"), b"[package]\nname = \"demo\"".to_vec());
+        assert!(strategy.call(&blob, &[]).is_empty());
+        
+        let json = Language::find_by_name("JSON").unwrap();
+        let blob = FileBlob::from_data(Path::new("package.json"), b"{\"name\": \"demo\"}".to_vec());
+        assert_eq!(strategy.call(&blob, std::slice::from_ref(json)), vec![json.clone()]);
+        
+        let blob = FileBlob::from_data(Path::new("Cargo.lock"), b"version = 3".to_vec());
+        assert!(strategy.call(&blob, &[]).is_empty());
+    }
+    
+    #[test]
+    fn test_manifest_strategy_with_candidates() {
+        let blob = FileBlob::from_data(Path::new("Gemfile"), b"gem \"rails\"".to_vec());
+        let strategy = Manifest;
+        
+        let ruby = Language::find_by_name("Ruby").unwrap();
+        let python = Language::find_by_name("Python").unwrap();
+        
+        let languages = strategy.call(&blob, &[ruby.clone(), python.clone()]);
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].name, "Ruby");
+        
+        let languages = strategy.call(&blob, std::slice::from_ref(python));
+        assert!(languages.is_empty());
+    }
+    
+    #[test]
+    fn test_manifest_detection() {
+        // Manifests are detected as their own format, not the project language
+        let manifests: &[(&str, &[u8], &str)] = &[
+            ("Cargo.toml", b"[package]\nname = \"demo\"\nversion = \"0.1.0\"\n", "TOML"),
+            ("package.json", b"{\n  \"name\": \"demo\",\n  \"version\": \"1.0.0\"\n}\n", "JSON"),
+            ("go.mod", b"module example.com/demo\n\ngo 1.21\n", "Go Module"),
+            ("Gemfile", b"source \"https://rubygems.org\"\ngem \"rails\"\n", "Ruby"),
+            ("setup.py", b"from setuptools import setup\n\nsetup(name=\"demo\")\n", "Python"),
+            ("pyproject.toml", b"[project]\nname = \"demo\"\n", "TOML"),
+            ("pom.xml", b"<?xml version=\"1.0\"?>\n<project>\n</project>\n", "Maven POM"),
+            ("build.gradle", b"plugins {\n    id 'java'\n}\n", "Gradle"),
+            ("build.gradle.kts", b"plugins {\n    kotlin(\"jvm\")\n}\n", "Kotlin"),
+            ("composer.json", b"{\n  \"name\": \"demo/demo\",\n  \"require\": {}\n}\n", "JSON"),
+            ("mix.exs", b"defmodule Demo.MixProject do\n  use Mix.Project\nend\n", "Elixir"),
+            ("Package.swift", b"// swift-tools-version:5.5\nimport PackageDescription\n", "Swift"),
+            ("pubspec.yaml", b"name: demo\nversion: 1.0.0\n", "YAML"),
+        ];
+        
+        for (manifest, _) in MANIFESTS {
+            assert!(manifests.iter().any(|(name, _, _)| name == manifest), "{} is not covered", manifest);
+        }
+        
+        for (name, data, expected) in manifests {
+            let blob = FileBlob::from_data(Path::new(name), data.to_vec());
+            assert_eq!(crate::detect(&blob, false).map(|lang| lang.name), Some(expected.to_string()), "{}", name);
+        }
+        
+        assert!(crate::strategies().unwrap().iter().all(|strategy| strategy.name() != "Manifest"));
+    }
+}
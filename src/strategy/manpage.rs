@@ -43,6 +43,11 @@ impl Strategy for Manpage {
         
         Vec::new()
     }
+    
+    fn is_definitive(&self) -> bool {
+        // Passes earlier candidates through, so it must not end detection
+        false
+    }
 }
 
 #[cfg(test)]
@@ -3,10 +3,14 @@
 //! This module contains various strategies for detecting the language
 //! of a file based on different criteria.
 
+pub mod composite;
+pub mod content_type;
 pub mod extension;
 pub mod filename;
+pub mod manifest;
 pub mod manpage;
 pub mod modeline;
+pub mod script;
 pub mod shebang;
 pub mod xml;
 
@@ -18,10 +22,16 @@ use crate::language::Language;
 pub enum StrategyType {
     /// Modeline-based strategy
     Modeline(modeline::Modeline),
+    /// HTTP Content-Type strategy
+    ContentType(content_type::ContentType),
     /// Filename-based strategy
     Filename(filename::Filename),
+    /// Manifest-based strategy
+    Manifest(manifest::Manifest),
     /// Shebang-based strategy
     Shebang(shebang::Shebang),
+    /// Content-based strategy for extensionless scripts
+    Script(script::Script),
     /// Extension-based strategy
     Extension(extension::Extension),
     /// XML detection strategy
@@ -32,6 +42,92 @@ pub enum StrategyType {
     Heuristics(crate::heuristics::Heuristics),
     /// Classifier-based strategy
     Classifier(crate::classifier::Classifier),
+    /// Strategy combining the results of other strategies
+    Composite(composite::Composite),
+}
+
+impl StrategyType {
+    /// Get the name of the strategy, e.g. `"Extension"`
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrategyType::Modeline(_) => "Modeline",
+            StrategyType::ContentType(_) => "ContentType",
+            StrategyType::Filename(_) => "Filename",
+            StrategyType::Manifest(_) => "Manifest",
+            StrategyType::Shebang(_) => "Shebang",
+            StrategyType::Script(_) => "Script",
+            StrategyType::Extension(_) => "Extension",
+            StrategyType::Xml(_) => "Xml",
+            StrategyType::Manpage(_) => "Manpage",
+            StrategyType::Heuristics(_) => "Heuristics",
+            StrategyType::Classifier(_) => "Classifier",
+            StrategyType::Composite(_) => "Composite",
+        }
+    }
+}
+
+// Strategies are compared by kind only: two `Extension` strategies are
+// equivalent regardless of their configuration.
+impl PartialEq for StrategyType {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl Eq for StrategyType {}
+
+impl std::hash::Hash for StrategyType {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+    }
+}
+
+/// Get the strategies most likely to matter for files with an extension
+///
+/// This is a hint, not a restriction: the strategies are returned in
+/// detection order, keeping Shebang only when one of the extension's
+/// languages has interpreters, Xml and Manpage only for extensions of XML
+/// and Roff, and Heuristics and Classifier only for extensions that have
+/// a disambiguation rule or belong to several languages. Modeline,
+/// ContentType and Filename don't depend on the extension, and Script only
+/// handles extensionless files, so they are left out. Unknown extensions
+/// get every strategy.
+///
+/// # Arguments
+///
+/// * `ext` - The extension, with or without the leading dot
+///
+/// # Returns
+///
+/// * `Vec<StrategyType>` - The relevant strategies, in detection order
+pub fn detect_strategies_for_extension(ext: &str) -> Vec<StrategyType> {
+    let ext = ext.to_lowercase();
+    let ext = if ext.starts_with('.') { ext } else { format!(".{}", ext) };
+    
+    let strategies = crate::strategies().unwrap_or_default();
+    let languages = Language::find_by_extension_all(&format!("file{}", ext));
+    if languages.is_empty() {
+        return strategies.to_vec();
+    }
+    
+    let has_language = |names: &[&str]| languages.iter().any(|lang| names.contains(&lang.name.as_str()));
+    let ambiguous = languages.len() > 1;
+    
+    strategies.iter()
+        .filter(|strategy| match strategy {
+            StrategyType::Modeline(_) | StrategyType::ContentType(_)
+            | StrategyType::Filename(_) | StrategyType::Manifest(_)
+            | StrategyType::Script(_) => false,
+            StrategyType::Shebang(_) => languages.iter().any(|lang| !lang.interpreters.is_empty()),
+            StrategyType::Extension(_) => true,
+            StrategyType::Xml(_) => has_language(&["XML"]),
+            StrategyType::Manpage(_) => has_language(&["Roff", "Roff Manpage"]),
+            StrategyType::Heuristics(heuristics) => ambiguous || heuristics.coverage().contains(&ext),
+            StrategyType::Classifier(_) => ambiguous,
+            StrategyType::Composite(_) => true,
+        })
+        .cloned()
+        .collect()
 }
 
 /// Trait for language detection strategies
@@ -47,19 +143,91 @@ pub trait Strategy: Send + Sync {
     ///
     /// * `Vec<Language>` - Languages that match the blob according to this strategy
     fn call<B: BlobHelper + ?Sized>(&self, blob: &B, candidates: &[Language]) -> Vec<Language>;
+    
+    /// Whether a single result from this strategy ends detection.
+    ///
+    /// Non-definitive strategies only narrow the candidate list for the
+    /// strategies that follow them.
+    fn is_definitive(&self) -> bool {
+        true
+    }
 }
 
 impl Strategy for StrategyType {
     fn call<B: BlobHelper + ?Sized>(&self, blob: &B, candidates: &[Language]) -> Vec<Language> {
         match self {
             StrategyType::Modeline(strategy) => strategy.call(blob, candidates),
+            StrategyType::ContentType(strategy) => strategy.call(blob, candidates),
             StrategyType::Filename(strategy) => strategy.call(blob, candidates),
+            StrategyType::Manifest(strategy) => strategy.call(blob, candidates),
             StrategyType::Shebang(strategy) => strategy.call(blob, candidates),
+            StrategyType::Script(strategy) => strategy.call(blob, candidates),
             StrategyType::Extension(strategy) => strategy.call(blob, candidates),
             StrategyType::Xml(strategy) => strategy.call(blob, candidates),
             StrategyType::Manpage(strategy) => strategy.call(blob, candidates),
             StrategyType::Heuristics(strategy) => strategy.call(blob, candidates),
             StrategyType::Classifier(strategy) => strategy.call(blob, candidates),
+            StrategyType::Composite(strategy) => strategy.call(blob, candidates),
+        }
+    }
+    
+    fn is_definitive(&self) -> bool {
+        match self {
+            StrategyType::Modeline(strategy) => strategy.is_definitive(),
+            StrategyType::ContentType(strategy) => strategy.is_definitive(),
+            StrategyType::Filename(strategy) => strategy.is_definitive(),
+            StrategyType::Manifest(strategy) => strategy.is_definitive(),
+            StrategyType::Shebang(strategy) => strategy.is_definitive(),
+            StrategyType::Script(strategy) => strategy.is_definitive(),
+            StrategyType::Extension(strategy) => strategy.is_definitive(),
+            StrategyType::Xml(strategy) => strategy.is_definitive(),
+            StrategyType::Manpage(strategy) => strategy.is_definitive(),
+            StrategyType::Heuristics(strategy) => strategy.is_definitive(),
+            StrategyType::Classifier(strategy) => strategy.is_definitive(),
+            StrategyType::Composite(strategy) => strategy.is_definitive(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn names(strategies: &[StrategyType]) -> Vec<&'static str> {
+        strategies.iter().map(StrategyType::name).collect()
+    }
+    
+    #[test]
+    fn test_detect_strategies_for_extension() {
+        assert_eq!(names(&detect_strategies_for_extension(".py")), ["Shebang", "Extension"]);
+        assert_eq!(names(&detect_strategies_for_extension("PY")), ["Shebang", "Extension"]);
+        
+        let header = names(&detect_strategies_for_extension(".h"));
+        assert!(header.contains(&"Heuristics"));
+        assert!(header.contains(&"Classifier"));
+        assert!(!header.contains(&"Filename"));
+        
+        // Unknown extensions get no hint
+        assert_eq!(detect_strategies_for_extension(".nosuchext").len(), crate::strategies().unwrap().len());
+    }
+    
+    #[test]
+    fn test_strategy_type_hash() {
+        use std::collections::HashSet;
+        
+        let strategies: HashSet<StrategyType> = crate::strategies().unwrap().iter()
+            .chain(crate::strategies().unwrap())
+            .cloned()
+            .collect();
+        assert_eq!(strategies.len(), crate::strategies().unwrap().len());
+        
+        assert_eq!(
+            StrategyType::ContentType(content_type::ContentType::default()),
+            StrategyType::ContentType(content_type::ContentType::from_header("text/x-ruby"))
+        );
+        assert_ne!(
+            StrategyType::Xml(xml::Xml),
+            StrategyType::Manpage(manpage::Manpage)
+        );
+    }
+}
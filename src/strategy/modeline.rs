@@ -11,9 +11,12 @@ use crate::language::Language;
 use crate::strategy::Strategy;
 
 lazy_static::lazy_static! {
-    // Updated Emacs modeline regex to handle both formats:
-    // -*- mode: ruby -*-  and -*-ruby-*-
-    static ref EMACS_MODELINE: Regex = Regex::new(r"(?i)-\*-(?:\s*(?:mode:\s*)?([^:;\s]+)(?:;|(?:\s*-\*-))|\s*(?:[^:]*?:\s*[^;]*?;)*?\s*mode\s*:\s*([^;]+?)(?:;|\s*-\*-))").unwrap();
+    // Emacs modeline delimiters; the attribute list between them is
+    // either a bare mode (-*-ruby-*-) or `key: value;` pairs
+    static ref EMACS_MODELINE: Regex = Regex::new(r"-\*-(.*?)-\*-").unwrap();
+    
+    // The `mode` attribute anywhere within the Emacs attribute list
+    static ref EMACS_MODE_ATTRIBUTE: Regex = Regex::new(r"(?i)(?:^|[;\s])mode\s*:\s*([^;\s]+)").unwrap();
     
     // Simplified Vim modeline regex
     static ref VIM_MODELINE: Regex = Regex::new(r"(?i)(?:vi|vim|ex)(?:m)?:.+(?:ft|filetype|syntax)\s*=\s*([a-z0-9]+)").unwrap();
@@ -37,18 +40,22 @@ impl Modeline {
     ///
     /// * `Option<String>` - The detected language name, if found
     fn modeline(content: &str) -> Option<String> {
-        // Updated to handle both capture groups in the regex
+        // Try Emacs modeline first, looking inside the -*- ... -*- markers
         if let Ok(Some(captures)) = EMACS_MODELINE.captures(content) {
-            // Check first capture group (for -*-ruby-*- format)
-            if let Some(mode) = captures.get(1) {
-                let mode_str = mode.as_str().trim();
-                return Some(mode_str.to_string());
-            }
-            
-            // Check second capture group (for -*- mode: ruby -*- format)
-            if let Some(mode) = captures.get(2) {
-                let mode_str = mode.as_str().trim();
-                return Some(mode_str.to_string());
+            if let Some(attributes) = captures.get(1) {
+                let attributes = attributes.as_str().trim();
+                
+                if attributes.contains(':') {
+                    // -*- coding: utf-8; mode: ruby; -*- format
+                    if let Ok(Some(mode)) = EMACS_MODE_ATTRIBUTE.captures(attributes) {
+                        if let Some(mode) = mode.get(1) {
+                            return Some(mode.as_str().to_string());
+                        }
+                    }
+                } else if !attributes.is_empty() {
+                    // -*-ruby-*- format
+                    return Some(attributes.trim_end_matches(';').trim().to_string());
+                }
             }
         }
         
@@ -148,6 +155,16 @@ mod tests {
         
         let content = "-*- foo:bar; mode: python; -*-\nprint('hello')";
         assert_eq!(Modeline::modeline(content), Some("python".to_string()));
+        
+        let content = "# -*- coding: utf-8; mode: python; -*-\nprint('hello')";
+        assert_eq!(Modeline::modeline(content), Some("python".to_string()));
+        
+        let content = "# -*- coding: utf-8; mode: ruby -*-\nputs 'hello'";
+        assert_eq!(Modeline::modeline(content), Some("ruby".to_string()));
+        
+        // Attributes without a mode should not produce a match
+        let content = "# -*- coding: utf-8 -*-\nputs 'hello'";
+        assert_eq!(Modeline::modeline(content), None);
     }
     
     #[test]
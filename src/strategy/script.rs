@@ -0,0 +1,116 @@
+//! Script detection strategy.
+//!
+//! This strategy detects interpreted scripts that have neither a shebang
+//! nor an extension from the construct their content starts with.
+
+use fancy_regex::Regex;
+
+use crate::blob::BlobHelper;
+use crate::language::Language;
+use crate::strategy::Strategy;
+
+// Maximum bytes to read when looking for the first line
+const SCRIPT_CONSIDER_BYTES: usize = 4096;
+
+lazy_static::lazy_static! {
+    // Patterns for the first non-blank line, mapped to the language they identify
+    static ref SCRIPT_PATTERNS: Vec<(Regex, &'static str)> = vec![
+        (Regex::new(r"^\(ns\s+[\w.*+!?'<>=-]+").unwrap(), "Clojure"),
+        (Regex::new(r"^local\s+function\s+[\w.:]+\s*\(").unwrap(), "Lua"),
+        (Regex::new(r"^module\s+[A-Z][\w.]*\s*(?:\(|where\b)").unwrap(), "Haskell"),
+        (Regex::new(r"^defmodule\s+[A-Z][\w.]*\s+do\b").unwrap(), "Elixir"),
+        (Regex::new(r"^<\?php\b").unwrap(), "PHP"),
+    ];
+}
+
+/// Script detection strategy for extensionless files
+#[derive(Debug, Clone)]
+pub struct Script;
+
+impl Script {
+    /// Get the first non-blank line of a file
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The file data
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The trimmed line, if the data starts with valid UTF-8 text
+    fn first_line(data: &[u8]) -> Option<&str> {
+        let data = &data[..std::cmp::min(data.len(), SCRIPT_CONSIDER_BYTES)];
+        let content = match std::str::from_utf8(data) {
+            Ok(s) => s,
+            // Keep the valid prefix if the limit split a character
+            Err(err) => std::str::from_utf8(&data[..err.valid_up_to()]).ok()?,
+        };
+        
+        content.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+    }
+}
+
+impl Strategy for Script {
+    fn call<B: BlobHelper + ?Sized>(&self, blob: &B, candidates: &[Language]) -> Vec<Language> {
+        // Files with an extension are left to the Extension strategy
+        if blob.extension().is_some() || blob.is_binary() || blob.is_symlink() {
+            return Vec::new();
+        }
+        
+        let line = match Self::first_line(blob.data()) {
+            Some(line) => line,
+            None => return Vec::new(),
+        };
+        
+        for (pattern, name) in SCRIPT_PATTERNS.iter() {
+            if pattern.is_match(line).unwrap_or(false) {
+                return Language::find_by_name(name)
+                    .filter(|language| candidates.is_empty() || candidates.contains(language))
+                    .map(|language| vec![language.clone()])
+                    .unwrap_or_default();
+            }
+        }
+        
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blob::FileBlob;
+    use std::path::Path;
+    
+    fn detect(name: &str, content: &str) -> Vec<String> {
+        let blob = FileBlob::from_data(Path::new(name), content.as_bytes().to_vec());
+        Script.call(&blob, &[]).into_iter().map(|language| language.name).collect()
+    }
+    
+    #[test]
+    fn test_script_patterns() {
+        for (_, name) in SCRIPT_PATTERNS.iter() {
+            assert!(Language::find_by_name(name).is_some(), "unknown language {}", name);
+        }
+        
+        assert_eq!(detect("core", "(ns my-app.core\n  (:require [clojure.string]))\n"), ["Clojure"]);
+        assert_eq!(detect("init", "\nlocal function setup(opts)\nend\n"), ["Lua"]);
+        assert_eq!(detect("Main", "module Main where\n\nmain = putStrLn \"hi\"\n"), ["Haskell"]);
+        assert_eq!(detect("Main", "module Data.Tree (Tree(..)) where\n"), ["Haskell"]);
+        assert_eq!(detect("app", "defmodule MyApp do\nend\n"), ["Elixir"]);
+        assert_eq!(detect("index", "<?php\necho 'hi';\n"), ["PHP"]);
+        
+        assert!(detect("notes", "module notes for the meeting\n").is_empty());
+        assert!(detect("empty", "\n\n").is_empty());
+    }
+    
+    #[test]
+    fn test_script_requires_no_extension() {
+        assert!(detect("core.txt", "(ns my-app.core)\n").is_empty());
+        
+        let blob = FileBlob::from_data(Path::new("init"), b"local function setup()\nend\n".to_vec());
+        let python = Language::find_by_name("Python").unwrap().clone();
+        assert!(Script.call(&blob, &[python]).is_empty());
+        assert_eq!(blob.language().unwrap().name, "Lua");
+    }
+}
@@ -4,19 +4,25 @@
 //! beginning of a file.
 
 use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 use fancy_regex::Regex;
 
 use crate::blob::BlobHelper;
 use crate::language::Language;
 use crate::strategy::Strategy;
+use crate::Result;
+
+// Bytes read from the start of a file by `Shebang::interpreter_from_path`
+const SHEBANG_CONSIDER_BYTES: usize = 512;
 
 lazy_static::lazy_static! {
     // Regex for extracting interpreter from shebang
     static ref SHEBANG_REGEX: Regex = Regex::new(r"^#!\s*(?:/usr/bin/env\s+)?(?:.*/)?([^/\s]+)").unwrap();
     
-    // Regex for handling /usr/bin/env with arguments
-    static ref ENV_ARGS_REGEX: Regex = Regex::new(r"^#!\s*\S+\s+env\s+(?:-\S+\s+)*([^\s-][^\s]*)").unwrap();
+    // Regex for handling /usr/bin/env, skipping options (-S, -u) and variable assignments
+    static ref ENV_ARGS_REGEX: Regex = Regex::new(r"^#!\s*(?:\S*/)?env\s+(?:(?:-\S*|\w+=\S*)\s+)*([^\s-]\S*)").unwrap();
     
     // Regex for multiline shebang hacks using exec
     static ref EXEC_REGEX: Regex = Regex::new(r#"exec (\w+)[\s'\"]+\$0[\s'\"]+\$@"#).unwrap();
@@ -54,23 +60,17 @@ impl Shebang {
             None => return None,
         };
         
-        // Special case for env with -S flag which is causing problems
-        if first_line.contains("/env -S ") {
-            let after_s = first_line.split("-S ").nth(1)?;
-            let interpreter = after_s.split_whitespace().next()?;
-            
-            if interpreter == "python2.7" {
-                return Some("python2".to_string());
-            }
-            return Some(interpreter.to_string());
-        }
-        
-        // Regular env without flags
-        if first_line.contains("/env ") && !first_line.contains("-") {
-            if let Ok(Some(captures)) = SHEBANG_REGEX.captures(first_line) {
-                if let Some(interpreter) = captures.get(1) {
-                    return Some(interpreter.as_str().to_string());
+        // env, possibly with options preceding the interpreter name
+        if let Ok(Some(captures)) = ENV_ARGS_REGEX.captures(first_line) {
+            if let Some(interpreter) = captures.get(1) {
+                let interpreter = Path::new(interpreter.as_str())
+                    .file_name()
+                    .and_then(|name| name.to_str())?;
+                
+                if interpreter == "python2.7" {
+                    return Some("python2".to_string());
                 }
+                return Some(interpreter.to_string());
             }
         }
         
@@ -101,6 +101,36 @@ impl Shebang {
         
         None
     }
+    
+    /// Extract the interpreter from a file's shebang line, reading only the start of the file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<String>>` - The extracted interpreter name, if found, or an error if the file can't be read
+    pub fn interpreter_from_path(path: &Path) -> Result<Option<String>> {
+        let mut head = Vec::with_capacity(SHEBANG_CONSIDER_BYTES);
+        File::open(path)?
+            .take(SHEBANG_CONSIDER_BYTES as u64)
+            .read_to_end(&mut head)?;
+        
+        Ok(Self::interpreter(&head))
+    }
+    
+    /// Get every interpreter that identifies a language
+    ///
+    /// # Returns
+    ///
+    /// * `HashSet<String>` - The interpreters listed in `languages.yml`
+    pub fn supported_interpreters() -> HashSet<String> {
+        Language::all()
+            .iter()
+            .flat_map(|language| language.interpreters.iter().cloned())
+            .collect()
+    }
 }
 
 impl Strategy for Shebang {
@@ -172,6 +202,25 @@ mod tests {
         Ok(())
     }
     
+    #[test]
+    fn test_interpreter_from_path() -> crate::Result<()> {
+        let dir = tempdir()?;
+        
+        let script = dir.path().join("run");
+        let mut content = b"#!/usr/bin/env ruby\n".to_vec();
+        content.extend(std::iter::repeat_n(b'#', 1 << 20));
+        std::fs::write(&script, content)?;
+        assert_eq!(Shebang::interpreter_from_path(&script)?, Some("ruby".to_string()));
+        
+        let plain = dir.path().join("plain");
+        std::fs::write(&plain, "no shebang here\n")?;
+        assert_eq!(Shebang::interpreter_from_path(&plain)?, None);
+        
+        assert!(Shebang::interpreter_from_path(&dir.path().join("missing")).is_err());
+        
+        Ok(())
+    }
+    
     #[test]
     fn test_interpreter_extraction() {
         // Simple shebang
@@ -190,6 +239,20 @@ mod tests {
         let content = b"#!/usr/bin/env -S python -u\nprint('hello')";
         assert_eq!(Shebang::interpreter(content), Some("python".to_string()));
         
+        // Arguments after the interpreter are ignored
+        let content = b"#!/usr/bin/python -u\nprint('hello')";
+        assert_eq!(Shebang::interpreter(content), Some("python".to_string()));
+        
+        let content = b"#!/usr/bin/env python -u\nprint('hello')";
+        assert_eq!(Shebang::interpreter(content), Some("python".to_string()));
+        
+        // Options before the interpreter are skipped
+        let content = b"#!/usr/bin/env -u python\nprint('hello')";
+        assert_eq!(Shebang::interpreter(content), Some("python".to_string()));
+        
+        let content = b"#!/usr/bin/env -i PATH=/usr/bin ruby\nputs 'hello'";
+        assert_eq!(Shebang::interpreter(content), Some("ruby".to_string()));
+        
         // With exec trick
         let content = b"#!/bin/sh\nexec perl \"$0\" \"$@\"\nprint('hello')";
         assert_eq!(Shebang::interpreter(content), Some("perl".to_string()));
@@ -226,4 +289,13 @@ mod tests {
         
         Ok(())
     }
+    
+    #[test]
+    fn test_supported_interpreters() {
+        let interpreters = Shebang::supported_interpreters();
+        assert!(interpreters.contains("python3"));
+        assert!(interpreters.contains("ruby"));
+        assert!(!interpreters.contains("not-an-interpreter"));
+        assert!(interpreters.iter().all(|interpreter| !Language::find_by_interpreter(interpreter).is_empty()));
+    }
 }
\ No newline at end of file
@@ -33,6 +33,11 @@ impl Strategy for Xml {
         
         Vec::new()
     }
+    
+    fn is_definitive(&self) -> bool {
+        // Passes earlier candidates through, so it must not end detection
+        false
+    }
 }
 
 #[cfg(test)]
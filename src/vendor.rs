@@ -6,6 +6,9 @@
 use fancy_regex::Regex;
 use std::path::Path;
 
+use crate::repository::{GitAttributeOverride, GitAttributeRules};
+use crate::Result;
+
 lazy_static::lazy_static! {
     // Regular expression patterns for vendored paths (from vendor.yml)
     pub static ref VENDOR_REGEX: Regex = {
@@ -68,6 +71,110 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Convert a `.gitattributes` glob pattern into an anchored regular expression
+///
+/// Patterns without a slash match the file's basename anywhere in the tree,
+/// others match the full path relative to the repository root. A trailing
+/// slash matches everything below that directory, and `/**/` matches zero
+/// or more directories.
+///
+/// # Arguments
+///
+/// * `pattern` - The glob pattern
+///
+/// # Returns
+///
+/// * `String` - The equivalent regular expression
+pub fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = if pattern.contains('/') {
+        String::from("^")
+    } else {
+        String::from("(^|/)")
+    };
+    
+    let mut pattern = pattern.trim_start_matches('/').to_string();
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+    
+    let mut chars = pattern.chars().peekable();
+    let mut segment_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if segment_start && chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            },
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        segment_start = c == '/';
+    }
+    regex.push('$');
+    
+    regex
+}
+
+/// Matcher for vendored paths, combining the standard vendor patterns
+/// with repository-specific ones
+#[derive(Debug, Default)]
+pub struct VendorMatcher {
+    /// Linguist overrides from `.gitattributes`, in file order
+    attributes: GitAttributeRules,
+}
+
+impl VendorMatcher {
+    /// Create a matcher using only the standard vendor patterns
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Create a matcher that honors `linguist-vendored` and
+    /// `-linguist-vendored` in a `.gitattributes` file
+    ///
+    /// As in git, the last line that matches a path decides, and
+    /// `-linguist-vendored` also applies to paths matching a standard
+    /// vendor pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The `.gitattributes` file content
+    ///
+    /// # Returns
+    ///
+    /// * `Result<VendorMatcher>` - The matcher
+    pub fn from_gitattributes(content: &str) -> Result<Self> {
+        let attributes = GitAttributeOverride::compile(content)
+            .into_iter()
+            .filter(|(_, attribute)| attribute.vendored.is_some())
+            .collect();
+        
+        Ok(Self { attributes })
+    }
+    
+    /// Check if a path is vendored
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The repository-relative path to check
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if `.gitattributes` marks the path vendored, or doesn't
+    ///   mention it and it matches a standard vendor pattern
+    pub fn is_vendored(&self, path: &str) -> bool {
+        GitAttributeOverride::for_path(&self.attributes, path)
+            .vendored
+            .unwrap_or_else(|| is_vendored(path))
+    }
+}
+
 /// Check if a path is a vendored file
 ///
 /// # Arguments
@@ -99,4 +206,58 @@ mod tests {
         assert!(!is_vendored("lib/utils.js"));
         assert!(!is_vendored("app/components/button.js"));
     }
+    
+    #[test]
+    fn test_glob_to_regex() {
+        assert_eq!(glob_to_regex("*.js"), r"(^|/)[^/]*\.js$");
+        assert_eq!(glob_to_regex("/lib/**"), r"^lib/.*$");
+        assert_eq!(glob_to_regex("lib/"), r"^lib/.*$");
+        assert_eq!(glob_to_regex("a/**/b"), r"^a/(.*/)?b$");
+        
+        let matches = |glob, path| regex::Regex::new(&glob_to_regex(glob)).unwrap().is_match(path);
+        assert!(matches("a/**/b", "a/b"));
+        assert!(matches("a/**/b", "a/x/y/b"));
+        assert!(!matches("a/**/b", "a/xb"));
+        assert!(matches("**/gen/*.c", "gen/main.c"));
+        assert!(matches("**/gen/*.c", "src/gen/main.c"));
+        assert!(matches("a**/b", "ax/y/b"));
+    }
+    
+    #[test]
+    fn test_vendor_matcher_from_gitattributes() -> Result<()> {
+        let content = "third_party_libs/** linguist-vendored\n\
+            *.generated.js linguist-vendored=true\n\
+            src/** -linguist-vendored\n";
+        let matcher = VendorMatcher::from_gitattributes(content)?;
+        
+        assert!(matcher.is_vendored("third_party_libs/foo/bar.c"));
+        assert!(matcher.is_vendored("app/api.generated.js"));
+        
+        // Standard patterns still apply
+        assert!(matcher.is_vendored("node_modules/react/index.js"));
+        
+        assert!(!matcher.is_vendored("src/main.js"));
+        assert!(!VendorMatcher::new().is_vendored("third_party_libs/foo/bar.c"));
+        
+        // The last matching line wins
+        let matcher = VendorMatcher::from_gitattributes("*.js linguist-vendored
+src/** -linguist-vendored
+")?;
+        assert!(matcher.is_vendored("lib/a.js"));
+        assert!(!matcher.is_vendored("src/a.js"));
+        
+        let matcher = VendorMatcher::from_gitattributes("src/** -linguist-vendored
+*.js linguist-vendored
+")?;
+        assert!(matcher.is_vendored("src/a.js"));
+        assert!(!matcher.is_vendored("src/a.c"));
+        
+        // Standard patterns can be un-vendored
+        let matcher = VendorMatcher::from_gitattributes("vendor/** -linguist-vendored
+")?;
+        assert!(!matcher.is_vendored("vendor/lib.rb"));
+        assert!(matcher.is_vendored("node_modules/react/index.js"));
+        
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,62 @@
+//! Reloading language data.
+//!
+//! `reload` replaces the process-wide language data, so this runs in its
+//! own test binary instead of alongside the unit tests.
+
+use std::path::Path;
+
+use linguist::blob::FileBlob;
+use linguist::data::languages::reload;
+use linguist::{detect_first_match, detect_with_config, DetectionConfig};
+use linguist::language::Language;
+use linguist::Result;
+
+const LANGUAGES_YML: &str = include_str!("../data/languages.yml");
+
+#[test]
+fn test_reload() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("languages.yml");
+    
+    let extra = "Linguist Reload Test:\n  type: programming\n  extensions:\n  - \".linguistreloadtest\"\n  language_id: 999999999\n";
+    std::fs::write(&path, format!("{}\n{}", LANGUAGES_YML, extra))?;
+    
+    let blob = FileBlob::from_data(Path::new("main.linguistreloadtest"), b"hello\n".to_vec());
+    let detect = || detect_first_match(&blob, false).map(|(language, strategy)| (language.name, strategy));
+    assert_eq!(detect(), None);
+    
+    let cached = DetectionConfig::default().use_global_cache(true);
+    assert_eq!(detect_with_config(&blob, &cached), None);
+    
+    let rust = Language::find_by_name("Rust").unwrap();
+    reload(&path)?;
+    
+    let reloaded = Language::find_by_name("Linguist Reload Test").unwrap();
+    assert_eq!(reloaded.language_id, 999999999);
+    assert_eq!(Language::find_by_extension("test.linguistreloadtest")[0].name, "Linguist Reload Test");
+    
+    // Detection picks up extensions that only exist in the reloaded data
+    assert_eq!(detect(), Some(("Linguist Reload Test".to_string(), "Extension")));
+    assert_eq!(detect_with_config(&blob, &cached).unwrap().name, "Linguist Reload Test");
+    
+    // Reloading an unchanged file reuses the data loaded before
+    reload(&path)?;
+    assert!(std::ptr::eq(Language::find_by_name("Linguist Reload Test").unwrap(), reloaded));
+    
+    // Languages from before the reload stay usable
+    assert_eq!(rust.name, "Rust");
+    assert_eq!(Language::find_by_name("Rust").unwrap(), rust);
+    
+    // Invalid files leave the data untouched
+    std::fs::write(&path, "- not: [a mapping")?;
+    assert!(reload(&path).is_err());
+    assert!(Language::find_by_name("Linguist Reload Test").is_some());
+    
+    std::fs::write(&path, LANGUAGES_YML)?;
+    reload(&path)?;
+    assert!(Language::find_by_name("Linguist Reload Test").is_none());
+    assert!(std::ptr::eq(Language::find_by_name("Rust").unwrap(), rust));
+    assert_eq!(detect(), None);
+    
+    Ok(())
+}